@@ -0,0 +1,120 @@
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::GameConfig,
+    neural_networks::{
+        brain::NeuralNetwork,
+        generation::{EvolutionSettings, FitnessHistory, Generation},
+    },
+    rng::GameRng,
+};
+
+// Bumped whenever the layout below changes, older files are refused.
+const CHECKPOINT_VERSION: u32 = 1;
+
+pub const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+// Everything needed to continue training exactly where a generation ended.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    pub generation_number: u32,
+    // Sorted by fitness, best first.
+    pub neural_networks: Vec<(NeuralNetwork, f32)>,
+    pub rng: GameRng,
+    pub evolution_settings: EvolutionSettings,
+    // Gameplay values the population was trained with.
+    pub config: GameConfig,
+    pub fitness_history: FitnessHistory,
+    // Best score seen by the level generator, which sets the curriculum difficulty.
+    pub level_best_score: u32,
+}
+
+// Read on its own first, so a file from another version is reported as such
+// rather than as a parse error.
+#[derive(Deserialize)]
+struct CheckpointVersion {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(error) => write!(f, "cannot access checkpoint: {}", error),
+            CheckpointError::Json(error) => write!(f, "invalid checkpoint: {}", error),
+            CheckpointError::UnsupportedVersion(version) => write!(
+                f,
+                "checkpoint version {} is not supported, expected {}",
+                version, CHECKPOINT_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for CheckpointError {}
+
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> Self {
+        CheckpointError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for CheckpointError {
+    fn from(error: serde_json::Error) -> Self {
+        CheckpointError::Json(error)
+    }
+}
+
+impl Checkpoint {
+    pub fn new(
+        generation: &Generation,
+        rng: &GameRng,
+        evolution_settings: &EvolutionSettings,
+        config: &GameConfig,
+        fitness_history: &FitnessHistory,
+        level_best_score: u32,
+    ) -> Checkpoint {
+        Checkpoint {
+            version: CHECKPOINT_VERSION,
+            generation_number: generation.generation_number,
+            neural_networks: generation.neural_networks.clone(),
+            rng: rng.clone(),
+            evolution_settings: evolution_settings.clone(),
+            config: config.clone(),
+            fitness_history: fitness_history.clone(),
+            level_best_score,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Checkpoint, CheckpointError> {
+        let data = fs::read_to_string(path)?;
+
+        let CheckpointVersion { version } = serde_json::from_str(&data)?;
+        if version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CheckpointError> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn generation(&self) -> Generation {
+        Generation {
+            neural_networks: self.neural_networks.clone(),
+            generation_number: self.generation_number,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::{
+    config::CONFIG_FILE,
+    level::LevelPreset,
+    neural_networks::{crossover::CrossoverMethod, selection::SelectionMethod},
+};
+
+#[derive(Parser)]
+#[command(about = "Flappy bird trained by a neural network population")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Evolve a population of neural network birds.
+    Train(TrainArgs),
+    /// Play a single bird with the space bar.
+    Play {
+        /// Let a simple script fly the bird instead.
+        #[arg(long)]
+        autopilot: bool,
+
+        #[command(flatten)]
+        game: GameArgs,
+    },
+    /// Play against a saved network, both birds fly through the same pipes.
+    Race {
+        /// Network saved by `train`, e.g. neural_network_save.json.
+        network: PathBuf,
+
+        #[command(flatten)]
+        game: GameArgs,
+    },
+    /// Replay one saved network in a window.
+    Watch {
+        /// Network saved by `train`, e.g. neural_network_save.json.
+        network: PathBuf,
+
+        #[command(flatten)]
+        game: GameArgs,
+    },
+    /// Benchmark one saved network headless and print its scores.
+    Eval {
+        /// Network saved by `train`, e.g. neural_network_save.json.
+        network: PathBuf,
+
+        /// Number of runs, each on a different pipe layout.
+        #[arg(long, default_value_t = 10)]
+        episodes: u32,
+
+        /// Steps of 1/60 s after which a run ends as completed, so a network
+        /// that never dies does not run forever.
+        #[arg(long, default_value_t = 36_000, value_parser = clap::value_parser!(u32).range(1..))]
+        max_steps: u32,
+
+        #[command(flatten)]
+        game: GameArgs,
+    },
+}
+
+#[derive(Args)]
+pub struct TrainArgs {
+    /// Train without a window or renderer.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Stop after this many generations, train forever when omitted.
+    #[arg(long)]
+    pub generations: Option<u32>,
+
+    /// Directory receiving the best network and the checkpoint.
+    #[arg(long, default_value = ".")]
+    pub output: PathBuf,
+
+    /// Continue training from a checkpoint file.
+    #[arg(long, value_name = "FILE")]
+    pub resume: Option<PathBuf>,
+
+    // The settings below are restored from the checkpoint when resuming.
+    /// Birds per generation.
+    #[arg(long, default_value_t = 500, conflicts_with = "resume")]
+    pub population: usize,
+
+    /// How far mutated weights move toward a random value, from 0 to 1.
+    #[arg(long, default_value_t = 0.10, conflicts_with = "resume")]
+    pub mutation: f32,
+
+    /// How the genes of two parents are mixed into a child.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CrossoverMethod::Uniform,
+        conflicts_with = "resume"
+    )]
+    pub crossover: CrossoverMethod,
+
+    /// How parents are picked: truncation:K among the K fittest, tournament:K
+    /// as the best of K random birds, or roulette in proportion to fitness.
+    #[arg(long, default_value = "truncation:10", conflicts_with = "resume")]
+    pub selection: SelectionMethod,
+
+    /// Fittest birds copied unmutated into the next generation, at most the population.
+    #[arg(long, default_value_t = 1, conflicts_with = "resume")]
+    pub elites: usize,
+
+    #[command(flatten)]
+    pub game: GameArgs,
+}
+
+// Settings shared by every subcommand.
+#[derive(Args)]
+pub struct GameArgs {
+    /// Seed for pipes, initial weights and mutations, random when omitted.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Gameplay values, reloaded whenever the file changes.
+    #[arg(long, default_value = CONFIG_FILE)]
+    pub config: PathBuf,
+
+    /// Overrides `gravity` from the config file.
+    #[arg(long)]
+    pub gravity: Option<f32>,
+
+    /// Pixels between two pipes, overrides `pipe_spacing` from the config file.
+    #[arg(long)]
+    pub pipe_spacing: Option<f32>,
+
+    /// Difficulty curve of the pipes, overrides `level` from the config file.
+    #[arg(long, value_enum)]
+    pub level: Option<LevelPreset>,
+
+    /// Course file, e.g. assets/courses/regression.course.json, played instead
+    /// of generated pipes.
+    #[arg(long, value_name = "FILE")]
+    pub course: Option<PathBuf>,
+}
@@ -1,89 +1,117 @@
-use std::collections::HashSet;
-
 use bevy::{prelude::*, sprite::collide_aabb::collide};
 
 use crate::{
-    components::Collider,
+    components::{Collider, Obstacle},
+    config::GameConfig,
+    pipe::part_translation,
     player::{
-        components::{Player, Score},
+        components::{PassedGates, Player, Score},
         events::PlayerDieEvent,
     },
-    PIPE_SIZE, PIPE_SPRITE_SCALE, PLAYER_SIZE, PLAYER_SPRITE_SCALE,
+    simulation::{SimulationLabel, SimulationStage},
 };
 
 pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(player_collision_system);
+        app.add_system_to_stage(
+            SimulationStage,
+            player_collision_system
+                .label(SimulationLabel::Collision)
+                .after(SimulationLabel::Movement),
+        );
     }
 }
 
+// Every pipe and trigger is checked against every player, triggers stay in
+// place so that each bird scores on its own.
 fn player_collision_system(
-    mut commands: Commands,
-    mut player_query: Query<(&Transform, Entity, &mut Score), With<Player>>,
-    collide_query: Query<(&Transform, &Collider, Entity), With<Collider>>,
+    config: Res<GameConfig>,
+    mut player_query: Query<(&Transform, Entity, &mut Score, &mut PassedGates), With<Player>>,
+    collide_query: Query<(&Transform, &Collider, &Parent)>,
+    obstacle_query: Query<(&Transform, &Obstacle)>,
     mut writer: EventWriter<PlayerDieEvent>,
 ) {
-    let mut collider_to_despawn = HashSet::new();
-    for (player_transform, player_entity, mut score) in player_query.iter_mut() {
-        for (collide_transform, collide_collider, entity) in collide_query.iter() {
+    for (player_transform, player_entity, mut score, mut passed_gates) in player_query.iter_mut() {
+        for (collide_transform, collide_collider, parent) in collide_query.iter() {
+            let (obstacle_transform, obstacle) = match obstacle_query.get(parent.get()) {
+                Ok(obstacle) => obstacle,
+                Err(_) => continue,
+            };
+            let collide_translation = part_translation(obstacle_transform, collide_transform);
+
             match collide_collider {
                 Collider::Loss => collide_loss(
+                    &config,
+                    obstacle,
                     player_transform,
-                    collide_transform,
+                    collide_translation,
                     &mut writer,
                     player_entity,
                 ),
-                Collider::Win => {
-                    collide_win(player_transform, collide_transform, &mut score);
-                    collider_to_despawn.insert(entity);
-                }
+                Collider::Win => collide_win(
+                    &config,
+                    obstacle,
+                    player_transform,
+                    collide_translation,
+                    &mut score,
+                    &mut passed_gates,
+                ),
             }
         }
     }
+}
 
-    for entity in collider_to_despawn.iter() {
-        commands.entity(*entity).despawn();
+// Hitbox of a collider, a trigger fills the gap between the pipes.
+pub fn collider_extent(config: &GameConfig, obstacle: &Obstacle, collider: &Collider) -> Vec2 {
+    match collider {
+        Collider::Loss => config.pipe_extent(),
+        Collider::Win => Vec2::new(config.pipe_extent().x, obstacle.gap_size),
     }
 }
 
-fn collide_win(player_transform: &Transform, collide_transform: &Transform, score: &mut Score) {
+// A gate counts once the bird is inside it and has reached its middle.
+fn collide_win(
+    config: &GameConfig,
+    obstacle: &Obstacle,
+    player_transform: &Transform,
+    collide_translation: Vec3,
+    score: &mut Score,
+    passed_gates: &mut PassedGates,
+) {
+    if passed_gates.has_passed(obstacle.id)
+        || collide_translation.x > player_transform.translation.x
+    {
+        return;
+    }
+
     let collision = collide(
         player_transform.translation,
-        Vec2::new(
-            PLAYER_SIZE.0 * PLAYER_SPRITE_SCALE,
-            PLAYER_SIZE.1 * PLAYER_SPRITE_SCALE,
-        ),
-        collide_transform.translation,
-        Vec2::new(
-            PLAYER_SIZE.0 * PLAYER_SPRITE_SCALE,
-            PLAYER_SIZE.1 * PLAYER_SPRITE_SCALE,
-        ),
+        config.player_extent(),
+        collide_translation,
+        collider_extent(config, obstacle, &Collider::Win),
     );
 
     if collision.is_some() {
         score.0 += 1;
+        passed_gates.last = Some(obstacle.id);
     }
 }
 
 fn collide_loss(
+    config: &GameConfig,
+    obstacle: &Obstacle,
     player_transform: &Transform,
-    collide_transform: &Transform,
+    collide_translation: Vec3,
     writer: &mut EventWriter<PlayerDieEvent>,
     player_entity: Entity,
 ) {
     let collision = collide(
         player_transform.translation,
-        Vec2::new(
-            PLAYER_SIZE.0 * PLAYER_SPRITE_SCALE,
-            PLAYER_SIZE.1 * PLAYER_SPRITE_SCALE,
-        ),
-        collide_transform.translation,
-        Vec2::new(
-            PIPE_SIZE.0 * PIPE_SPRITE_SCALE,
-            PIPE_SIZE.1 * PIPE_SPRITE_SCALE,
-        ),
+        config.player_extent(),
+        collide_translation,
+        collider_extent(config, obstacle, &Collider::Loss),
     );
 
     if collision.is_some() {
@@ -1,8 +1,14 @@
-use bevy::{prelude::Component, time::Timer};
+use bevy::{
+    prelude::{Component, Vec3},
+    time::Timer,
+};
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::Inspectable;
 
 use crate::GameStates;
 
 #[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 pub struct Velocity {
     pub x: f32,
     pub y: f32,
@@ -11,7 +17,44 @@ pub struct Velocity {
 #[derive(Component)]
 pub struct Pipe;
 
+// Parent of the two pipes and the scoring trigger of one gap, moved as a whole.
 #[derive(Component)]
+pub struct Obstacle {
+    // Counts up from 0 in spawn order.
+    pub id: u32,
+    pub gap_center: f32,
+    // Distance between the edges of the two pipes.
+    pub gap_size: f32,
+}
+
+impl Obstacle {
+    pub fn top(&self) -> f32 {
+        self.gap_center + self.gap_size / 2.
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.gap_center - self.gap_size / 2.
+    }
+}
+
+// Translations at the last two simulation steps, rendering blends between them.
+#[derive(Component)]
+pub struct Interpolated {
+    pub previous: Vec3,
+    pub current: Vec3,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec3) -> Interpolated {
+        Interpolated {
+            previous: translation,
+            current: translation,
+        }
+    }
+}
+
+#[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 pub struct AffectedByGravity {
     pub is_affected: bool,
 }
@@ -31,3 +74,9 @@ pub enum Collider {
 pub struct TextGameState {
     pub state: GameStates,
 }
+
+#[derive(Component)]
+pub struct TextSimulationSpeed;
+
+#[derive(Component)]
+pub struct TextScore;
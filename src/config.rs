@@ -0,0 +1,422 @@
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::LevelPreset,
+    neural_networks::{activation::Activation, brain::NeuralNetwork},
+    player::sensors::Sensor,
+    Gravity,
+};
+
+pub const CONFIG_FILE: &str = "config.json";
+
+// How often the config file is checked for edits.
+const CONFIG_POLL_INTERVAL: f32 = 1.;
+
+// Gameplay values, read from the config file at startup and whenever it changes.
+// Speeds and velocities are fractions of `base_speed` per second.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    // Play area in pixels, only read at startup. The window opens at this size
+    // and headless runs keep it.
+    pub window_size: (f32, f32),
+    pub base_speed: f32,
+    // Sprite sheet cell and pipe mesh size, the textures are built from them at
+    // startup so a live edit only changes hitboxes.
+    pub pipe_size: (f32, f32),
+    pub pipe_sprite_scale: f32,
+    pub player_size: (f32, f32),
+    pub player_sprite_scale: f32,
+    pub gravity: f32,
+    // Pixels scrolled between two pipes.
+    pub pipe_spacing: f32,
+    pub jump_velocity: f32,
+    pub pipe_velocity: f32,
+    // Distance from the gap center to the center of each pipe.
+    pub gap_offset: f32,
+    // Gap centers are drawn between minus and plus this height.
+    pub gap_center_range: f32,
+    // Scales the gap, spacing and speed values above as a round goes on.
+    pub level: LevelPreset,
+    pub brain: BrainConfig,
+}
+
+// Shape of the trained networks. Only read at startup, a saved network has to
+// keep the inputs it was trained with.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrainConfig {
+    pub hidden_layers: Vec<usize>,
+    // Network inputs in this order, one per sensor and one per ray for `rays`.
+    pub sensors: Vec<Sensor>,
+    pub rays: RayConfig,
+    // Activations of new networks, saved ones keep their own.
+    pub hidden_activation: Activation,
+    pub output_activation: Activation,
+}
+
+// Fan of rays cast ahead of each bird.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RayConfig {
+    pub count: usize,
+    // Angle in degrees between the lowest and the highest ray, centered on the
+    // flight direction.
+    pub spread: f32,
+    // Reach in pixels, farther obstacles are not seen.
+    pub length: f32,
+}
+
+impl Default for RayConfig {
+    fn default() -> Self {
+        RayConfig {
+            count: 5,
+            spread: 120.,
+            length: 400.,
+        }
+    }
+}
+
+impl RayConfig {
+    // Unit vectors from the lowest ray to the highest.
+    pub fn directions(&self) -> Vec<Vec2> {
+        (0..self.count)
+            .map(|i| {
+                let t = if self.count > 1 {
+                    i as f32 / (self.count - 1) as f32 - 0.5
+                } else {
+                    0.
+                };
+                let angle = (t * self.spread).to_radians();
+                Vec2::new(angle.cos(), angle.sin())
+            })
+            .collect()
+    }
+}
+
+impl Default for BrainConfig {
+    fn default() -> Self {
+        BrainConfig {
+            hidden_layers: vec![6],
+            sensors: vec![
+                Sensor::BirdY,
+                Sensor::NextGapCenter,
+                Sensor::NextPipeDistance,
+            ],
+            rays: RayConfig::default(),
+            hidden_activation: Activation::Step,
+            output_activation: Activation::Step,
+        }
+    }
+}
+
+impl BrainConfig {
+    pub fn input_count(&self) -> usize {
+        self.sensors
+            .iter()
+            .map(|sensor| match sensor {
+                Sensor::Rays => self.rays.count,
+                _ => 1,
+            })
+            .sum()
+    }
+
+    // Neurons per layer, from the inputs to the single jump output.
+    pub fn topology(&self) -> Vec<usize> {
+        let mut topology = vec![self.input_count()];
+        topology.extend(&self.hidden_layers);
+        topology.push(1);
+        topology
+    }
+
+    // One per level of `topology`.
+    pub fn activations(&self) -> Vec<Activation> {
+        let mut activations = vec![self.hidden_activation; self.hidden_layers.len()];
+        activations.push(self.output_activation);
+        activations
+    }
+
+    // Networks loaded from a file may come from another config.
+    pub fn check(&self, neural_network: &NeuralNetwork) -> Result<(), ConfigError> {
+        if neural_network.input_count() != self.input_count() {
+            return Err(ConfigError::InputMismatch {
+                inputs: neural_network.input_count(),
+                expected: self.input_count(),
+            });
+        }
+        if neural_network.output_count() != 1 {
+            return Err(ConfigError::OutputMismatch(neural_network.output_count()));
+        }
+        Ok(())
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            window_size: (598., 676.),
+            base_speed: 500.,
+            pipe_size: (32., 128.),
+            pipe_sprite_scale: 3.5,
+            player_size: (719., 612.),
+            player_sprite_scale: 0.1,
+            gravity: 3.,
+            pipe_spacing: 750.,
+            jump_velocity: 0.65,
+            pipe_velocity: -0.5,
+            gap_offset: 300.,
+            gap_center_range: 100.,
+            level: LevelPreset::default(),
+            brain: BrainConfig::default(),
+        }
+    }
+}
+
+// Values given on the command line, they win over the file on every reload.
+#[derive(Clone, Copy, Default)]
+pub struct ConfigOverrides {
+    pub gravity: Option<f32>,
+    pub pipe_spacing: Option<f32>,
+    pub level: Option<LevelPreset>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Invalid {
+        field: &'static str,
+        reason: &'static str,
+    },
+    InputMismatch {
+        inputs: usize,
+        expected: usize,
+    },
+    OutputMismatch(usize),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "cannot read config: {}", error),
+            ConfigError::Json(error) => write!(f, "invalid config: {}", error),
+            ConfigError::Invalid { field, reason } => {
+                write!(f, "invalid config: `{}` {}", field, reason)
+            }
+            ConfigError::InputMismatch { inputs, expected } => write!(
+                f,
+                "network takes {} inputs but the configured sensors give {}",
+                inputs, expected
+            ),
+            ConfigError::OutputMismatch(outputs) => {
+                write!(f, "network has {} outputs, expected 1", outputs)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(error: serde_json::Error) -> Self {
+        ConfigError::Json(error)
+    }
+}
+
+impl GameConfig {
+    // A missing file gives the defaults, fields left out of the file too.
+    pub fn load(path: &Path, overrides: ConfigOverrides) -> Result<GameConfig, ConfigError> {
+        let mut config = match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => GameConfig::default(),
+            Err(error) => return Err(error.into()),
+        };
+
+        config.apply(overrides);
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn apply(&mut self, overrides: ConfigOverrides) {
+        if let Some(gravity) = overrides.gravity {
+            self.gravity = gravity;
+        }
+        if let Some(pipe_spacing) = overrides.pipe_spacing {
+            self.pipe_spacing = pipe_spacing;
+        }
+        if let Some(level) = overrides.level {
+            self.level = level;
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let positive = [
+            ("window_size", self.window_size.0.min(self.window_size.1)),
+            ("base_speed", self.base_speed),
+            ("pipe_size", self.pipe_size.0.min(self.pipe_size.1)),
+            ("pipe_sprite_scale", self.pipe_sprite_scale),
+            ("player_size", self.player_size.0.min(self.player_size.1)),
+            ("player_sprite_scale", self.player_sprite_scale),
+            ("pipe_spacing", self.pipe_spacing),
+            ("jump_velocity", self.jump_velocity),
+            ("gap_offset", self.gap_offset),
+        ];
+        for (field, value) in positive {
+            check(
+                field,
+                value.is_finite() && value > 0.,
+                "must be greater than 0",
+            )?;
+        }
+
+        let not_negative = [
+            ("gravity", self.gravity),
+            ("gap_center_range", self.gap_center_range),
+        ];
+        for (field, value) in not_negative {
+            check(
+                field,
+                value.is_finite() && value >= 0.,
+                "must not be negative",
+            )?;
+        }
+
+        check(
+            "gap_offset",
+            2. * self.gap_offset > self.pipe_extent().y,
+            "must leave room between the pipes",
+        )?;
+
+        // Pipes spawn on the right and are despawned on the left.
+        check(
+            "pipe_velocity",
+            self.pipe_velocity.is_finite() && self.pipe_velocity < 0.,
+            "must be lower than 0",
+        )?;
+
+        check(
+            "brain.sensors",
+            !self.brain.sensors.is_empty(),
+            "must not be empty",
+        )?;
+        check(
+            "brain.hidden_layers",
+            self.brain.hidden_layers.iter().all(|size| *size > 0),
+            "must only hold sizes greater than 0",
+        )?;
+        check(
+            "brain.rays.count",
+            self.brain.rays.count > 0 || !self.brain.sensors.contains(&Sensor::Rays),
+            "must be greater than 0 when `rays` is a sensor",
+        )?;
+        check(
+            "brain.rays.spread",
+            (0. ..=360.).contains(&self.brain.rays.spread),
+            "must be between 0 and 360",
+        )?;
+        check(
+            "brain.rays.length",
+            self.brain.rays.length.is_finite() && self.brain.rays.length > 0.,
+            "must be greater than 0",
+        )
+    }
+
+    pub fn jump_speed(&self) -> f32 {
+        self.jump_velocity * self.base_speed
+    }
+
+    // On screen size of a player and of a pipe.
+    pub fn player_extent(&self) -> Vec2 {
+        Vec2::new(self.player_size.0, self.player_size.1) * self.player_sprite_scale
+    }
+
+    pub fn pipe_extent(&self) -> Vec2 {
+        Vec2::new(self.pipe_size.0, self.pipe_size.1) * self.pipe_sprite_scale
+    }
+}
+
+pub struct ConfigWatcher {
+    path: PathBuf,
+    overrides: ConfigOverrides,
+    modified: Option<SystemTime>,
+    timer: Timer,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf, overrides: ConfigOverrides) -> ConfigWatcher {
+        ConfigWatcher {
+            modified: modified(&path),
+            path,
+            overrides,
+            timer: Timer::from_seconds(CONFIG_POLL_INTERVAL, true),
+        }
+    }
+}
+
+fn check(field: &'static str, valid: bool, reason: &'static str) -> Result<(), ConfigError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid { field, reason })
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(config_reload_system);
+    }
+}
+
+// A broken edit is reported and the previous values are kept.
+fn config_reload_system(
+    time: Res<Time>,
+    mut watcher: ResMut<ConfigWatcher>,
+    mut config: ResMut<GameConfig>,
+    mut gravity: ResMut<Gravity>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let modified = modified(&watcher.path);
+    if modified == watcher.modified {
+        return;
+    }
+    watcher.modified = modified;
+
+    match GameConfig::load(&watcher.path, watcher.overrides) {
+        Ok(mut new_config) => {
+            if new_config.brain != config.brain {
+                eprintln!("Brain changes in {} need a restart", watcher.path.display());
+                new_config.brain = config.brain.clone();
+            }
+            gravity.amplitude = new_config.gravity;
+            // Spacing and level changes apply from the next pipe on.
+            *config = new_config;
+            println!("Reloaded {}", watcher.path.display());
+        }
+        Err(error) => eprintln!("Unable to reload {}: {}", watcher.path.display(), error),
+    }
+}
@@ -0,0 +1,178 @@
+use std::{error::Error, f32::consts::TAU, fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+// Hand-made obstacles read from a `.course.json` file, spawned in order instead
+// of generated ones.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Course {
+    // Starts over after the last obstacle, otherwise the course ends with it.
+    #[serde(rename = "loop", default)]
+    pub looping: bool,
+    pub obstacles: Vec<CourseObstacle>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CourseObstacle {
+    // Pixels scrolled since the previous obstacle, or since the round started.
+    pub spacing: f32,
+    pub gap_center: f32,
+    pub gap_size: f32,
+    #[serde(default)]
+    pub motion: Option<Motion>,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Motion {
+    // Moves the whole gap up and down around its center, `period` in seconds.
+    Oscillate { amplitude: f32, period: f32 },
+}
+
+impl Motion {
+    // Vertical offset of the obstacle after `elapsed` seconds.
+    pub fn offset(&self, elapsed: f32) -> f32 {
+        match *self {
+            Motion::Oscillate { amplitude, period } => amplitude * (TAU * elapsed / period).sin(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ObstacleMotion {
+    pub motion: Motion,
+    pub elapsed: f32,
+}
+
+#[derive(Debug)]
+pub enum CourseError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Empty,
+    Invalid {
+        obstacle: usize,
+        field: &'static str,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for CourseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CourseError::Io(error) => write!(f, "cannot read course: {}", error),
+            CourseError::Json(error) => write!(f, "invalid course: {}", error),
+            CourseError::Empty => write!(f, "course has no obstacles"),
+            CourseError::Invalid {
+                obstacle,
+                field,
+                reason,
+            } => write!(f, "obstacle {}: `{}` {}", obstacle, field, reason),
+        }
+    }
+}
+
+impl Error for CourseError {}
+
+impl From<io::Error> for CourseError {
+    fn from(error: io::Error) -> Self {
+        CourseError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for CourseError {
+    fn from(error: serde_json::Error) -> Self {
+        CourseError::Json(error)
+    }
+}
+
+impl Course {
+    // Read before the game starts, so a broken file is reported right away.
+    pub fn load(path: &Path) -> Result<Course, CourseError> {
+        let course: Course = serde_json::from_str(&fs::read_to_string(path)?)?;
+        course.validate()?;
+        Ok(course)
+    }
+
+    fn validate(&self) -> Result<(), CourseError> {
+        if self.obstacles.is_empty() {
+            return Err(CourseError::Empty);
+        }
+
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            let invalid = |field, reason| CourseError::Invalid {
+                obstacle: i,
+                field,
+                reason,
+            };
+
+            // A zero spacing would spawn a looping course all at once.
+            if !(obstacle.spacing.is_finite() && obstacle.spacing > 0.) {
+                return Err(invalid("spacing", "must be greater than 0"));
+            }
+            if !(obstacle.gap_size.is_finite() && obstacle.gap_size > 0.) {
+                return Err(invalid("gap_size", "must be greater than 0"));
+            }
+            if let Some(Motion::Oscillate { period, .. }) = obstacle.motion {
+                if !(period.is_finite() && period > 0.) {
+                    return Err(invalid("motion.period", "must be greater than 0"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Progress through the course of the current round.
+pub struct PlayedCourse {
+    course: Course,
+    spawned: usize,
+    // Scrolled distance at which the last obstacle spawned.
+    spawned_at: f32,
+    // Set once every obstacle of a course that does not loop is gone.
+    pub complete: bool,
+}
+
+impl PlayedCourse {
+    pub fn new(course: Course) -> PlayedCourse {
+        PlayedCourse {
+            course,
+            spawned: 0,
+            spawned_at: 0.,
+            complete: false,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.spawned = 0;
+        self.spawned_at = 0.;
+        self.complete = false;
+    }
+
+    // Obstacle due at `distance` scrolled since the round started, with how far
+    // past its spawn point the course already is.
+    pub fn next_obstacle(&mut self, distance: f32) -> Option<(CourseObstacle, f32)> {
+        let course = &self.course;
+        let obstacle = if course.looping {
+            &course.obstacles[self.spawned % course.obstacles.len()]
+        } else {
+            course.obstacles.get(self.spawned)?
+        };
+
+        let spawn_at = self.spawned_at + obstacle.spacing;
+        if distance < spawn_at {
+            return None;
+        }
+
+        self.spawned += 1;
+        self.spawned_at = spawn_at;
+        Some((obstacle.clone(), distance - spawn_at))
+    }
+
+    // Every obstacle of a course that does not loop has been spawned.
+    pub fn is_finished(&self) -> bool {
+        !self.course.looping && self.spawned >= self.course.obstacles.len()
+    }
+}
@@ -0,0 +1,288 @@
+use bevy::prelude::*;
+
+use crate::{
+    neural_networks::generation::{FitnessHistory, Generation, GenerationFitness},
+    player::components::Player,
+    simulation::{SimulationClock, SimulationSpeed, FIXED_TIMESTEP},
+    GameFont,
+};
+
+const CHART_SIZE: (f32, f32) = (200., 80.);
+
+// Dots drawn per line, spread evenly over the chart width.
+const CHART_POINTS: usize = 100;
+const CHART_POINT_SIZE: f32 = 2.;
+
+const BEST_COLOR: Color = Color::rgb(0.2, 0.8, 0.2);
+const MEAN_COLOR: Color = Color::rgb(1., 0.6, 0.);
+
+// Training overlay, toggled with H and moved to the next corner with C.
+pub struct DashboardPlugin;
+
+#[derive(Clone, Copy)]
+enum Corner {
+    TopRight,
+    BottomRight,
+    BottomLeft,
+    TopLeft,
+}
+
+impl Corner {
+    fn next(self) -> Corner {
+        match self {
+            Corner::TopRight => Corner::BottomRight,
+            Corner::BottomRight => Corner::BottomLeft,
+            Corner::BottomLeft => Corner::TopLeft,
+            Corner::TopLeft => Corner::TopRight,
+        }
+    }
+
+    fn position(self) -> UiRect<Val> {
+        let margin = Val::Px(5.);
+        // The top left corner leaves room for the speed readout.
+        match self {
+            Corner::TopRight => UiRect {
+                top: margin,
+                right: margin,
+                ..default()
+            },
+            Corner::BottomRight => UiRect {
+                bottom: margin,
+                right: margin,
+                ..default()
+            },
+            Corner::BottomLeft => UiRect {
+                bottom: margin,
+                left: margin,
+                ..default()
+            },
+            Corner::TopLeft => UiRect {
+                top: Val::Px(30.),
+                left: margin,
+                ..default()
+            },
+        }
+    }
+}
+
+struct Dashboard {
+    visible: bool,
+    corner: Corner,
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        Dashboard {
+            visible: true,
+            corner: Corner::TopRight,
+        }
+    }
+}
+
+#[derive(Component)]
+struct DashboardPanel;
+
+// Visibility is not inherited by child nodes, so every node of the panel carries this.
+#[derive(Component)]
+struct DashboardNode;
+
+#[derive(Component)]
+struct DashboardText;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ChartLine {
+    Best,
+    Mean,
+}
+
+#[derive(Component)]
+struct ChartPoint {
+    line: ChartLine,
+    index: usize,
+}
+
+impl Plugin for DashboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Dashboard>()
+            .add_startup_system_to_stage(StartupStage::PostStartup, dashboard_startup_system)
+            .add_system(dashboard_keyboard_system)
+            .add_system(dashboard_text_system)
+            .add_system(dashboard_chart_system);
+    }
+}
+
+fn dashboard_startup_system(
+    mut commands: Commands,
+    game_font: Res<GameFont>,
+    dashboard: Res<Dashboard>,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: dashboard.corner.position(),
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: UiRect::all(Val::Px(5.)),
+                ..default()
+            },
+            color: UiColor(Color::rgba(0., 0., 0., 0.5)),
+            ..default()
+        })
+        .insert(DashboardPanel)
+        .insert(DashboardNode)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: game_font.0.clone(),
+                        font_size: 16.,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(DashboardText)
+                .insert(DashboardNode);
+
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(CHART_SIZE.0), Val::Px(CHART_SIZE.1)),
+                        margin: UiRect {
+                            top: Val::Px(5.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    color: UiColor(Color::rgba(1., 1., 1., 0.1)),
+                    ..default()
+                })
+                .insert(DashboardNode)
+                .with_children(|chart| {
+                    for line in [ChartLine::Best, ChartLine::Mean] {
+                        for index in 0..CHART_POINTS {
+                            chart_point(chart, line, index);
+                        }
+                    }
+                });
+        });
+}
+
+fn chart_point(chart: &mut ChildBuilder, line: ChartLine, index: usize) {
+    let color = match line {
+        ChartLine::Best => BEST_COLOR,
+        ChartLine::Mean => MEAN_COLOR,
+    };
+
+    chart
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Px(CHART_POINT_SIZE), Val::Px(CHART_POINT_SIZE)),
+                ..default()
+            },
+            color: UiColor(color),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(ChartPoint { line, index });
+}
+
+fn dashboard_keyboard_system(
+    kb: Res<Input<KeyCode>>,
+    mut dashboard: ResMut<Dashboard>,
+    mut panel_query: Query<&mut Style, With<DashboardPanel>>,
+    mut query: Query<&mut Visibility, With<DashboardNode>>,
+) {
+    if kb.just_pressed(KeyCode::H) {
+        dashboard.visible = !dashboard.visible;
+    }
+    if kb.just_pressed(KeyCode::C) {
+        dashboard.corner = dashboard.corner.next();
+    }
+    if !dashboard.is_changed() {
+        return;
+    }
+
+    for mut style in panel_query.iter_mut() {
+        style.position = dashboard.corner.position();
+    }
+    for mut visibility in query.iter_mut() {
+        visibility.is_visible = dashboard.visible;
+    }
+}
+
+fn dashboard_text_system(
+    generation: Res<Generation>,
+    fitness_history: Res<FitnessHistory>,
+    clock: Res<SimulationClock>,
+    speed: Res<SimulationSpeed>,
+    player_query: Query<(), With<Player>>,
+    mut query: Query<&mut Text, With<DashboardText>>,
+) {
+    let (best, mean) = match fitness_history.0.last() {
+        Some(fitness) => (
+            format!("{:.1}", fitness.best),
+            format!("{:.1}", fitness.mean),
+        ),
+        None => ("-".to_owned(), "-".to_owned()),
+    };
+    let seconds = (clock.steps as f64 * FIXED_TIMESTEP as f64) as u64;
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!(
+            "Generation: {}\nAlive: {}\nBest fitness: {}\nMean fitness: {}\nSim time: {}:{:02}:{:02}\nSpeed: {}",
+            generation.generation_number,
+            player_query.iter().count(),
+            best,
+            mean,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            *speed
+        );
+    }
+}
+
+// Each line is sampled at evenly spaced points, interpolating between generations,
+// and scaled so the best fitness so far reaches the top.
+fn dashboard_chart_system(
+    dashboard: Res<Dashboard>,
+    fitness_history: Res<FitnessHistory>,
+    mut query: Query<(&mut Style, &mut Visibility, &ChartPoint)>,
+) {
+    if !fitness_history.is_changed() && !dashboard.is_changed() {
+        return;
+    }
+
+    let history = &fitness_history.0;
+    let max_fitness = history
+        .iter()
+        .map(|fitness| fitness.best)
+        .fold(0., f32::max);
+
+    for (mut style, mut visibility, chart_point) in query.iter_mut() {
+        if !dashboard.visible || history.is_empty() || max_fitness <= 0. {
+            visibility.is_visible = false;
+            continue;
+        }
+
+        let t = chart_point.index as f32 / (CHART_POINTS - 1) as f32;
+        let position = t * (history.len() - 1) as f32;
+        let previous = &history[position.floor() as usize];
+        let next = &history[position.ceil() as usize];
+        let value = |fitness: &GenerationFitness| match chart_point.line {
+            ChartLine::Best => fitness.best,
+            ChartLine::Mean => fitness.mean,
+        };
+        let fitness = value(previous) + (value(next) - value(previous)) * position.fract();
+
+        style.position = UiRect {
+            left: Val::Px(t * (CHART_SIZE.0 - CHART_POINT_SIZE)),
+            bottom: Val::Px(
+                (fitness / max_fitness).clamp(0., 1.) * (CHART_SIZE.1 - CHART_POINT_SIZE),
+            ),
+            ..default()
+        };
+        visibility.is_visible = true;
+    }
+}
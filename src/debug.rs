@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::{InspectorPlugin, RegisterInspectable, WorldInspectorPlugin};
+
+use crate::{
+    components::{AffectedByGravity, Velocity},
+    config::GameConfig,
+    neural_networks::brain::NeuralNetwork,
+    player::{
+        components::{Player, Score},
+        vision_plugin::RayHits,
+    },
+    Gravity, PipeSpawnSettings,
+};
+
+// Above pipes and players.
+const RAY_Z: f32 = 15.;
+const RAY_HIT_COLOR: Color = Color::rgba(1., 0.3, 0.3, 0.8);
+const RAY_MISS_COLOR: Color = Color::rgba(1., 1., 1., 0.3);
+
+// World inspector for tweaking physics and weights live, and an overlay of the
+// vision rays toggled with R, built with `--features debug`.
+pub struct DebugPlugin;
+
+#[derive(Component)]
+struct RaySegment;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(WorldInspectorPlugin::new())
+            .add_plugin(InspectorPlugin::<Gravity>::new())
+            .add_plugin(InspectorPlugin::<PipeSpawnSettings>::new())
+            .register_inspectable::<Velocity>()
+            .register_inspectable::<Score>()
+            .register_inspectable::<AffectedByGravity>()
+            .register_inspectable::<NeuralNetwork>()
+            .add_system(ray_overlay_system);
+    }
+}
+
+// One sprite per ray, respawned when the number of rays on screen changes.
+fn ray_overlay_system(
+    mut commands: Commands,
+    kb: Res<Input<KeyCode>>,
+    mut hidden: Local<bool>,
+    config: Res<GameConfig>,
+    player_query: Query<(&Transform, &RayHits), With<Player>>,
+    mut segment_query: Query<
+        (Entity, &mut Transform, &mut Sprite),
+        (With<RaySegment>, Without<Player>),
+    >,
+) {
+    if kb.just_pressed(KeyCode::R) {
+        *hidden = !*hidden;
+    }
+
+    let directions = config.brain.rays.directions();
+    let segments: Vec<(Transform, Sprite)> = if *hidden {
+        Vec::new()
+    } else {
+        player_query
+            .iter()
+            .flat_map(|(transform, ray_hits)| {
+                directions
+                    .iter()
+                    .zip(ray_hits.0.iter())
+                    .map(|(direction, hit)| ray_segment(&config, transform, *direction, *hit))
+            })
+            .collect()
+    };
+
+    if segments.len() != segment_query.iter().len() {
+        for (entity, _, _) in segment_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        for (transform, sprite) in segments {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    transform,
+                    sprite,
+                    ..default()
+                })
+                .insert(RaySegment);
+        }
+        return;
+    }
+
+    for ((_, mut transform, mut sprite), segment) in segment_query.iter_mut().zip(segments) {
+        *transform = segment.0;
+        *sprite = segment.1;
+    }
+}
+
+fn ray_segment(
+    config: &GameConfig,
+    player_transform: &Transform,
+    direction: Vec2,
+    hit: f32,
+) -> (Transform, Sprite) {
+    let start = player_transform.translation.truncate();
+    let length = hit * config.brain.rays.length;
+    let center = start + direction * length / 2.;
+
+    let transform = Transform {
+        translation: center.extend(RAY_Z),
+        rotation: Quat::from_rotation_z(direction.y.atan2(direction.x)),
+        ..default()
+    };
+    let sprite = Sprite {
+        color: if hit < 1. {
+            RAY_HIT_COLOR
+        } else {
+            RAY_MISS_COLOR
+        },
+        custom_size: Some(Vec2::new(length, 1.)),
+        ..default()
+    };
+    (transform, sprite)
+}
@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    components::Obstacle,
+    config::GameConfig,
+    player::{
+        components::{Controller, Player, Score},
+        events::PlayerDieEvent,
+        spawn_plugin::spawn_neural_network_player,
+    },
+    simulation::{SimulationLabel, SimulationStage},
+    GameStates, GameTextures, PipeSpawnSettings, RunMode,
+};
+
+// Runs other than training: when the bird dies a human gets the game over
+// screen, a network gets a cleared course and a new episode.
+pub struct EpisodePlugin;
+
+#[derive(Default)]
+pub struct EpisodeStats {
+    current_score: u32,
+    // Steps of the current episode, and whether it ran out of them.
+    steps: u32,
+    step_limit_reached: bool,
+    pub scores: Vec<u32>,
+    // Episodes that reached the end of a course file or the step limit.
+    completed: u32,
+}
+
+impl Plugin for EpisodePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EpisodeStats>().add_system_to_stage(
+            SimulationStage,
+            episode_score_system
+                .label(SimulationLabel::Evolution)
+                .after(SimulationLabel::Collision),
+        );
+
+        if app.world.resource::<RunMode>().is_interactive() {
+            app.add_system_set(
+                SystemSet::on_update(GameStates::Playing).with_system(episode_game_over_system),
+            );
+        } else {
+            app.add_system_to_stage(
+                SimulationStage,
+                episode_step_limit_system
+                    .label(SimulationLabel::Collision)
+                    .after(SimulationLabel::Movement),
+            )
+            .add_system_to_stage(
+                SimulationStage,
+                episode_end_system
+                    .label(SimulationLabel::Generation)
+                    .after(SimulationLabel::Evolution),
+            );
+        }
+    }
+}
+
+fn episode_score_system(
+    run_mode: Res<RunMode>,
+    mut reader: EventReader<PlayerDieEvent>,
+    query: Query<(&Score, &Controller), With<Player>>,
+    mut episode_stats: ResMut<EpisodeStats>,
+) {
+    let player_die_entities: HashSet<Entity> = reader
+        .iter()
+        .map(|player_die_event| player_die_event.0)
+        .collect();
+
+    for entity in player_die_entities {
+        if let Ok((score, controller)) = query.get(entity) {
+            if run_mode.keeps_score_of(*controller) {
+                episode_stats.current_score = episode_stats.current_score.max(score.0);
+            }
+        }
+    }
+}
+
+// Ends an `eval` run that outlasts its step limit, the bird is then done
+// rather than dead.
+fn episode_step_limit_system(
+    run_mode: Res<RunMode>,
+    mut episode_stats: ResMut<EpisodeStats>,
+    mut writer: EventWriter<PlayerDieEvent>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    let max_steps = match *run_mode {
+        RunMode::Eval { max_steps, .. } => max_steps,
+        _ => return,
+    };
+
+    episode_stats.steps += 1;
+    if episode_stats.steps >= max_steps {
+        episode_stats.step_limit_reached = true;
+        for entity in player_query.iter() {
+            writer.send(PlayerDieEvent(entity));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn episode_end_system(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    run_mode: Res<RunMode>,
+    game_textures: Option<Res<GameTextures>>,
+    mut episode_stats: ResMut<EpisodeStats>,
+    mut pipe_spawn_settings: ResMut<PipeSpawnSettings>,
+    mut exit: EventWriter<AppExit>,
+    player_query: Query<Entity, With<Player>>,
+    obstacle_query: Query<Entity, With<Obstacle>>,
+) {
+    let finished = matches!(
+        *run_mode,
+        RunMode::Eval { episodes, .. } if episode_stats.scores.len() as u32 >= episodes
+    );
+    if finished || !player_query.is_empty() {
+        return;
+    }
+
+    end_episode(&mut episode_stats, pipe_spawn_settings.course_complete());
+
+    for entity in obstacle_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    pipe_spawn_settings.restart(&config);
+
+    if let RunMode::Eval { episodes, .. } = *run_mode {
+        if episode_stats.scores.len() as u32 >= episodes {
+            print_summary(&episode_stats);
+            exit.send(AppExit);
+            return;
+        }
+    }
+
+    if let Some(neural_network) = run_mode.neural_network() {
+        spawn_neural_network_player(
+            &mut commands,
+            &config,
+            game_textures.as_deref(),
+            neural_network,
+        );
+    }
+}
+
+// Runs once per frame rather than per step, so the state is switched only once.
+fn episode_game_over_system(
+    run_mode: Res<RunMode>,
+    pipe_spawn_settings: Res<PipeSpawnSettings>,
+    mut state: ResMut<State<GameStates>>,
+    mut episode_stats: ResMut<EpisodeStats>,
+    player_query: Query<&Controller, With<Player>>,
+) {
+    if player_query
+        .iter()
+        .any(|controller| run_mode.keeps_score_of(*controller))
+    {
+        return;
+    }
+
+    end_episode(&mut episode_stats, pipe_spawn_settings.course_complete());
+    state.set(GameStates::GameOver).unwrap();
+}
+
+fn end_episode(episode_stats: &mut EpisodeStats, course_complete: bool) {
+    let score = episode_stats.current_score;
+    let outcome = if course_complete {
+        Some("course complete")
+    } else if episode_stats.step_limit_reached {
+        Some("step limit reached")
+    } else {
+        None
+    };
+    episode_stats.current_score = 0;
+    episode_stats.steps = 0;
+    episode_stats.step_limit_reached = false;
+    episode_stats.scores.push(score);
+
+    match outcome {
+        Some(outcome) => {
+            episode_stats.completed += 1;
+            println!(
+                "Episode {}: {} pipes, {}",
+                episode_stats.scores.len(),
+                score,
+                outcome
+            );
+        }
+        None => println!("Episode {}: {} pipes", episode_stats.scores.len(), score),
+    }
+}
+
+fn print_summary(episode_stats: &EpisodeStats) {
+    let scores = &episode_stats.scores;
+    let mean = scores.iter().sum::<u32>() as f32 / scores.len() as f32;
+    let best = scores.iter().max().unwrap_or(&0);
+    let worst = scores.iter().min().unwrap_or(&0);
+    println!(
+        "{} episodes: mean {:.2}, best {}, worst {}, {} completed",
+        scores.len(),
+        mean,
+        best,
+        worst,
+        episode_stats.completed
+    );
+}
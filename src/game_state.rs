@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::Obstacle, config::GameConfig, player::components::Player, GameStates,
+    PipeSpawnSettings, RunMode,
+};
+
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        // Only a human needs a start screen, networks start flying right away.
+        let initial_state = if app.world.resource::<RunMode>().is_interactive() {
+            GameStates::StartScreen
+        } else {
+            GameStates::Playing
+        };
+
+        app.add_state(initial_state)
+            .add_system(start_round_keyboard_system)
+            .add_system_set(
+                SystemSet::on_exit(GameStates::GameOver).with_system(clear_round_system),
+            );
+    }
+}
+
+fn start_round_keyboard_system(kb: Res<Input<KeyCode>>, mut state: ResMut<State<GameStates>>) {
+    if state.current() != &GameStates::Playing && kb.just_pressed(KeyCode::Space) {
+        state.set(GameStates::Playing).unwrap();
+    }
+}
+
+// The last round stays on screen behind the game over text until a new one starts.
+fn clear_round_system(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut pipe_spawn_settings: ResMut<PipeSpawnSettings>,
+    query: Query<Entity, Or<(With<Player>, With<Obstacle>)>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    pipe_spawn_settings.restart(&config);
+}
@@ -2,25 +2,32 @@ use bevy::prelude::*;
 
 use crate::{
     components::{AffectedByGravity, Velocity},
-    Gravity, BASE_SPEED,
+    config::GameConfig,
+    simulation::{SimulationLabel, SimulationStage, FIXED_TIMESTEP},
+    Gravity,
 };
 
 pub struct GravityPlugin;
 
 impl Plugin for GravityPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(gravity_system);
+        app.add_system_to_stage(
+            SimulationStage,
+            gravity_system
+                .label(SimulationLabel::Physics)
+                .after(SimulationLabel::Decision),
+        );
     }
 }
 
 fn gravity_system(
-    time: Res<Time>,
+    config: Res<GameConfig>,
     gravity: Res<Gravity>,
     mut query: Query<(&mut Velocity, &AffectedByGravity), With<AffectedByGravity>>,
 ) {
     for (mut velocity, affected_by_gravity) in query.iter_mut() {
         if affected_by_gravity.is_affected {
-            velocity.y -= gravity.amplitude * time.delta_seconds() * BASE_SPEED;
+            velocity.y -= gravity.amplitude * FIXED_TIMESTEP * config.base_speed;
         } else {
             velocity.y = 0.;
         }
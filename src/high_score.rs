@@ -0,0 +1,87 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    player::{
+        components::{Controller, Player, Score},
+        events::PlayerDieEvent,
+    },
+    simulation::{SimulationLabel, SimulationStage},
+    RunMode,
+};
+
+// Best score ever reached in one game mode, kept in its own file.
+pub struct HighScore {
+    path: PathBuf,
+    pub best: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HighScoreFile {
+    best: u32,
+}
+
+impl HighScore {
+    // A missing or unreadable file starts the mode over from 0.
+    pub fn load(run_mode: &RunMode) -> HighScore {
+        let path = PathBuf::from(format!("high_score_{}.json", run_mode.name()));
+        let best = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<HighScoreFile>(&data).ok())
+            .map_or(0, |high_score_file| high_score_file.best);
+
+        HighScore { path, best }
+    }
+
+    fn save(&self) {
+        let high_score_file = HighScoreFile { best: self.best };
+        if let Err(error) = fs::write(&self.path, serde_json::to_string(&high_score_file).unwrap())
+        {
+            eprintln!("Unable to save {}: {}", self.path.display(), error);
+        }
+    }
+}
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        let high_score = HighScore::load(app.world.resource::<RunMode>());
+
+        app.insert_resource(high_score).add_system_to_stage(
+            SimulationStage,
+            high_score_system
+                .label(SimulationLabel::Evolution)
+                .after(SimulationLabel::Collision),
+        );
+    }
+}
+
+// Written when a bird dies with a new record, which is rare once a mode has been played a while.
+fn high_score_system(
+    run_mode: Res<RunMode>,
+    mut high_score: ResMut<HighScore>,
+    mut reader: EventReader<PlayerDieEvent>,
+    query: Query<(&Score, &Controller), With<Player>>,
+) {
+    let player_die_entities: HashSet<Entity> = reader
+        .iter()
+        .map(|player_die_event| player_die_event.0)
+        .collect();
+
+    let best = player_die_entities
+        .into_iter()
+        .filter_map(|entity| query.get(entity).ok())
+        .filter(|(_, controller)| run_mode.keeps_score_of(**controller))
+        .map(|(score, _)| score.0)
+        .max();
+
+    if let Some(best) = best {
+        if best > high_score.best {
+            high_score.best = best;
+            high_score.save();
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use clap::ValueEnum;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+
+// Distance scrolled, in pixels, before a preset reaches its hardest values.
+const RAMP_DISTANCE: f32 = 40_000.;
+
+// Best score at which the curriculum reaches its hardest values.
+const CURRICULUM_SCORE: u32 = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelPreset {
+    // Wide gaps, slowly closing in to normal ones.
+    Easy,
+    // Starts with the config values and gets harder with distance.
+    #[default]
+    Normal,
+    Hard,
+    // From easy to hard as the best bird of the run scores, for training.
+    Curriculum,
+}
+
+// Multipliers of the config values: gap size, largest move of the gap center
+// from one obstacle to the next, distance between obstacles and scroll speed.
+#[derive(Clone, Copy)]
+struct Difficulty {
+    gap_size: f32,
+    gap_delta: f32,
+    spacing: f32,
+    speed: f32,
+}
+
+const EASY: Difficulty = Difficulty {
+    gap_size: 1.5,
+    gap_delta: 0.5,
+    spacing: 1.2,
+    speed: 0.8,
+};
+
+const NORMAL: Difficulty = Difficulty {
+    gap_size: 1.,
+    gap_delta: 1.,
+    spacing: 1.,
+    speed: 1.,
+};
+
+const HARD: Difficulty = Difficulty {
+    gap_size: 0.8,
+    gap_delta: 1.5,
+    spacing: 0.8,
+    speed: 1.3,
+};
+
+impl Difficulty {
+    fn lerp(self, other: Difficulty, t: f32) -> Difficulty {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Difficulty {
+            gap_size: lerp(self.gap_size, other.gap_size),
+            gap_delta: lerp(self.gap_delta, other.gap_delta),
+            spacing: lerp(self.spacing, other.spacing),
+            speed: lerp(self.speed, other.speed),
+        }
+    }
+}
+
+// Gap of the next obstacle and the distance to scroll before the one after it.
+pub struct NextObstacle {
+    pub gap_center: f32,
+    pub gap_size: f32,
+    pub spacing: f32,
+}
+
+// Lays out the obstacles of one round, started over whenever the course is cleared.
+#[derive(Default)]
+pub struct LevelGenerator {
+    distance: f32,
+    best_score: u32,
+    previous_center: f32,
+}
+
+impl LevelGenerator {
+    // Starts a new round. The best score is kept, so the curriculum carries its
+    // difficulty over from one generation to the next.
+    pub fn reset(&mut self) {
+        *self = LevelGenerator {
+            best_score: self.best_score,
+            ..LevelGenerator::default()
+        };
+    }
+
+    pub fn advance(&mut self, distance: f32) {
+        self.distance += distance;
+    }
+
+    // Pixels scrolled since the round started.
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn record_score(&mut self, score: u32) {
+        self.best_score = self.best_score.max(score);
+    }
+
+    pub fn best_score(&self) -> u32 {
+        self.best_score
+    }
+
+    fn difficulty(&self, preset: LevelPreset) -> Difficulty {
+        let by_distance = (self.distance / RAMP_DISTANCE).min(1.);
+        match preset {
+            LevelPreset::Easy => EASY.lerp(NORMAL, by_distance),
+            LevelPreset::Normal => NORMAL.lerp(HARD, by_distance),
+            LevelPreset::Hard => HARD,
+            LevelPreset::Curriculum => EASY.lerp(
+                HARD,
+                (self.best_score as f32 / CURRICULUM_SCORE as f32).min(1.),
+            ),
+        }
+    }
+
+    // Horizontal velocity of every obstacle, as a fraction of the base speed.
+    pub fn scroll_velocity(&self, config: &GameConfig) -> f32 {
+        config.pipe_velocity * self.difficulty(config.level).speed
+    }
+
+    pub fn next_obstacle<R: Rng + ?Sized>(
+        &mut self,
+        config: &GameConfig,
+        rng: &mut R,
+    ) -> NextObstacle {
+        let difficulty = self.difficulty(config.level);
+
+        // The gap drifts from the previous one, and stays within the config range.
+        let max_delta = 2. * config.gap_center_range * difficulty.gap_delta;
+        // An empty range panics, a zero range always centers the gap.
+        let delta = if max_delta > 0. {
+            rng.gen_range(-max_delta..max_delta)
+        } else {
+            0.
+        };
+        let gap_center =
+            (self.previous_center + delta).clamp(-config.gap_center_range, config.gap_center_range);
+        self.previous_center = gap_center;
+
+        NextObstacle {
+            gap_center,
+            gap_size: (2. * config.gap_offset - config.pipe_extent().y) * difficulty.gap_size,
+            spacing: config.pipe_spacing * difficulty.spacing,
+        }
+    }
+}
@@ -1,35 +1,71 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
 use bevy::{
+    input::InputPlugin,
     prelude::{shape::Box, *},
     render::texture::ImageSettings,
     window::PresentMode,
 };
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::Inspectable;
+use checkpoint::Checkpoint;
+use clap::Parser;
+use cli::{Cli, Command};
 use collision::CollisionPlugin;
+use config::{BrainConfig, ConfigOverrides, ConfigPlugin, ConfigWatcher, GameConfig};
+use course::{Course, PlayedCourse};
+use episode::EpisodePlugin;
+use game_state::GameStatePlugin;
 use gravity::GravityPlugin;
-use neural_networks::generation::Generation;
+use high_score::HighScorePlugin;
+use level::LevelGenerator;
+use neural_networks::{
+    brain::NeuralNetwork,
+    fitness::FitnessFunction,
+    generation::{EvolutionSettings, Generation},
+};
 use pipe::PipePlugin;
 use player::{
+    components::Controller,
     events::{PlayerDieEvent, SpawnPlayers},
     plugin::PlayerPlugin,
 };
+use rng::GameRng;
+use simulation::{SimulationPlugin, SimulationSpeed};
 use textdisplay::TextDisplayPlugin;
+use visualizer::VisualizerPlugin;
 
+mod checkpoint;
+mod cli;
 mod collision;
 mod components;
+mod config;
+mod course;
+mod dashboard;
+#[cfg(feature = "debug")]
+mod debug;
+mod episode;
+mod game_state;
 mod gravity;
+mod high_score;
+mod level;
 mod neural_networks;
 mod pipe;
 mod player;
+mod rng;
+mod simulation;
 mod textdisplay;
-
-const BASE_SPEED: f32 = 500.;
+mod visualizer;
 
 const PIPE_SPRITE: &str = "pipe.png";
-const PIPE_SIZE: (f32, f32) = (32., 128.);
-const PIPE_SPRITE_SCALE: f32 = 3.5;
 
 const PLAYER_SPRITE: &str = "player-spritesheet.png";
-const PLAYER_SIZE: (f32, f32) = (719., 612.);
-const PLAYER_SPRITE_SCALE: f32 = 0.1;
+
+const NEURAL_NETWORK_SAVE_FILE: &str = "neural_network_save.json";
 
 struct WinSize {
     w: f32,
@@ -44,65 +80,371 @@ struct GameTextures {
 
 struct GameFont(Handle<Font>);
 
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 struct Gravity {
     amplitude: f32,
 }
 
-#[derive(PartialEq, Debug)]
+// Required by the inspector, the config file sets the actual value.
+#[cfg(feature = "debug")]
+impl Default for Gravity {
+    fn default() -> Self {
+        Gravity {
+            amplitude: GameConfig::default().gravity,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum GameStates {
     Playing,
     GameOver,
     StartScreen,
 }
 
-struct GameState {
-    state: GameStates,
+// Spawns an obstacle each time the course has scrolled far enough, the level
+// generator picks the obstacle and the distance to the next one. A course file
+// given on the command line replaces the generator.
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+struct PipeSpawnSettings {
+    distance_left: f32,
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    level: LevelGenerator,
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    course: Option<PlayedCourse>,
 }
 
-struct PipeSpawnSettings {
-    timer: Timer,
+impl PipeSpawnSettings {
+    // Back to the first obstacle, once the previous round is cleared.
+    fn restart(&mut self, config: &GameConfig) {
+        self.distance_left = config.pipe_spacing;
+        self.level.reset();
+        if let Some(course) = &mut self.course {
+            course.restart();
+        }
+    }
+
+    // A course keeps the config speed, its obstacles are laid out by hand.
+    fn scroll_velocity(&self, config: &GameConfig) -> f32 {
+        match self.course {
+            Some(_) => config.pipe_velocity,
+            None => self.level.scroll_velocity(config),
+        }
+    }
+
+    fn course_complete(&self) -> bool {
+        self.course.as_ref().is_some_and(|course| course.complete)
+    }
+}
+
+#[cfg(feature = "debug")]
+impl Default for PipeSpawnSettings {
+    fn default() -> Self {
+        PipeSpawnSettings {
+            distance_left: GameConfig::default().pipe_spacing,
+            level: LevelGenerator::default(),
+            course: None,
+        }
+    }
+}
+
+// What the app was started for, picked by the command line subcommand.
+pub enum RunMode {
+    Train {
+        generation_limit: Option<u32>,
+        output: PathBuf,
+        // Best network of an earlier run in the same output directory.
+        saved: Option<NeuralNetwork>,
+    },
+    Play {
+        autopilot: bool,
+    },
+    // A human against a saved network.
+    Race(NeuralNetwork),
+    Watch(NeuralNetwork),
+    Eval {
+        neural_network: NeuralNetwork,
+        episodes: u32,
+        max_steps: u32,
+    },
 }
 
-// #[derive(Inspectable, Default)]
-// struct Data {
-//     query: InspectorQuery<Entity, With<NeuralNetwork>>,
-// }
+impl RunMode {
+    // Network driving the single bird of race, watch and eval runs.
+    pub fn neural_network(&self) -> Option<NeuralNetwork> {
+        match self {
+            RunMode::Race(neural_network) | RunMode::Watch(neural_network) => {
+                Some(neural_network.clone())
+            }
+            RunMode::Eval { neural_network, .. } => Some(neural_network.clone()),
+            RunMode::Train { .. } | RunMode::Play { .. } => None,
+        }
+    }
+
+    // Rounds started from the keyboard, with start and game over screens.
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, RunMode::Play { .. } | RunMode::Race(_))
+    }
+
+    // Whose score counts: the person or its autopilot in interactive rounds,
+    // in a race the network is only there to beat.
+    pub fn keeps_score_of(&self, controller: Controller) -> bool {
+        !self.is_interactive() || controller != Controller::NeuralNetwork
+    }
+
+    // Identifies the mode in file names, autopilot records are kept apart from human ones.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunMode::Train { .. } => "train",
+            RunMode::Play { autopilot: false } => "play",
+            RunMode::Play { autopilot: true } => "autopilot",
+            RunMode::Race(_) => "race",
+            RunMode::Watch(_) => "watch",
+            RunMode::Eval { .. } => "eval",
+        }
+    }
+}
 
 fn main() {
-    App::new()
-        .insert_resource(ClearColor(Color::rgb(0.34, 0.75, 0.79)))
-        .insert_resource(ImageSettings::default_nearest())
-        .insert_resource(GameState {
-            state: GameStates::Playing,
-        })
-        .insert_resource(PipeSpawnSettings {
-            timer: Timer::from_seconds(3.0, true),
+    let cli = Cli::parse();
+
+    let game_args = match &cli.command {
+        Command::Train(train_args) => &train_args.game,
+        Command::Play {
+            game: game_args, ..
+        }
+        | Command::Race {
+            game: game_args, ..
+        }
+        | Command::Watch {
+            game: game_args, ..
+        }
+        | Command::Eval {
+            game: game_args, ..
+        } => game_args,
+    };
+
+    let mut game_rng = match game_args.seed {
+        Some(seed) => GameRng::from_seed(seed),
+        None => GameRng::from_entropy(),
+    };
+    let config_overrides = ConfigOverrides {
+        gravity: game_args.gravity,
+        pipe_spacing: game_args.pipe_spacing,
+        level: game_args.level,
+    };
+    let config_file = game_args.config.clone();
+    let mut config = GameConfig::load(&config_file, config_overrides).unwrap_or_else(|error| {
+        eprintln!("Unable to load {}: {}", config_file.display(), error);
+        process::exit(1);
+    });
+    let course = game_args.course.as_ref().map(|path| {
+        Course::load(path).unwrap_or_else(|error| {
+            eprintln!("Unable to load {}: {}", path.display(), error);
+            process::exit(1);
         })
-        .insert_resource(Generation::new())
-        .insert_resource(WindowDescriptor {
-            title: "Flappy Rust".to_string(),
-            width: 598.0,
-            height: 676.0,
-            present_mode: PresentMode::AutoVsync,
-            ..Default::default()
+    });
+
+    let mut generation = Generation::new();
+    let mut fitness_history = None;
+    let mut evolution_settings = EvolutionSettings::default();
+    let mut pipe_spawn_settings = PipeSpawnSettings {
+        distance_left: config.pipe_spacing,
+        level: LevelGenerator::default(),
+        course: course.map(PlayedCourse::new),
+    };
+
+    let (run_mode, headless) = match cli.command {
+        Command::Train(train_args) => {
+            if train_args.elites > train_args.population {
+                eprintln!(
+                    "Unable to keep {} elites out of a population of {}",
+                    train_args.elites, train_args.population
+                );
+                process::exit(1);
+            }
+
+            evolution_settings.population_size = train_args.population;
+            evolution_settings.mutation_amount = train_args.mutation;
+            evolution_settings.crossover = train_args.crossover;
+            evolution_settings.selection = train_args.selection;
+            evolution_settings.elites = train_args.elites;
+
+            if let Some(path) = &train_args.resume {
+                let checkpoint = Checkpoint::load(path).unwrap_or_else(|error| {
+                    eprintln!("Unable to resume from {}: {}", path.display(), error);
+                    process::exit(1);
+                });
+
+                // Command line overrides still win, and live edits of the config
+                // file still apply once training goes on.
+                let mut saved_config = checkpoint.config.clone();
+                saved_config.apply(config_overrides);
+                if saved_config != config {
+                    println!(
+                        "Using the config saved in {} instead of {}",
+                        path.display(),
+                        config_file.display()
+                    );
+                }
+                for (neural_network, _) in &checkpoint.neural_networks {
+                    check_neural_network(&saved_config.brain, neural_network, path);
+                }
+
+                generation = checkpoint.generation();
+                game_rng = checkpoint.rng;
+                evolution_settings = checkpoint.evolution_settings;
+                config = saved_config;
+                fitness_history = Some(checkpoint.fitness_history);
+                pipe_spawn_settings
+                    .level
+                    .record_score(checkpoint.level_best_score);
+            }
+
+            fs::create_dir_all(&train_args.output).unwrap_or_else(|error| {
+                eprintln!(
+                    "Unable to create {}: {}",
+                    train_args.output.display(),
+                    error
+                );
+                process::exit(1);
+            });
+
+            let save_file = train_args.output.join(NEURAL_NETWORK_SAVE_FILE);
+            let saved = save_file
+                .exists()
+                .then(|| load_neural_network(&save_file, &config.brain));
+
+            (
+                RunMode::Train {
+                    generation_limit: train_args.generations,
+                    output: train_args.output,
+                    saved,
+                },
+                train_args.headless,
+            )
+        }
+        Command::Play { autopilot, .. } => (RunMode::Play { autopilot }, false),
+        Command::Race { network, .. } => (
+            RunMode::Race(load_neural_network(&network, &config.brain)),
+            false,
+        ),
+        Command::Watch { network, .. } => (
+            RunMode::Watch(load_neural_network(&network, &config.brain)),
+            false,
+        ),
+        Command::Eval {
+            network,
+            episodes,
+            max_steps,
+            ..
+        } => (
+            RunMode::Eval {
+                neural_network: load_neural_network(&network, &config.brain),
+                episodes,
+                max_steps,
+            },
+            true,
+        ),
+    };
+    println!("Seed: {}", game_rng.seed);
+
+    // The window starts at the configured size, headless runs keep it.
+    let (width, height) = config.window_size;
+    let gravity = Gravity {
+        amplitude: config.gravity,
+    };
+    let config_watcher = ConfigWatcher::new(config_file, config_overrides);
+
+    let mut app = App::new();
+
+    app.insert_resource(pipe_spawn_settings)
+        .insert_resource(game_rng)
+        .insert_resource(generation)
+        .insert_resource(FitnessFunction::default())
+        .insert_resource(evolution_settings)
+        .insert_resource(WinSize {
+            w: width,
+            h: height,
         })
-        .insert_resource(Gravity { amplitude: 3. })
+        .insert_resource(gravity)
+        .insert_resource(config)
+        .insert_resource(config_watcher)
+        .insert_resource(run_mode)
         .add_event::<PlayerDieEvent>()
-        .add_event::<SpawnPlayers>()
-        .add_plugins(DefaultPlugins)
-        .add_startup_system(setup_system)
-        .add_system(win_size_refresh_system)
+        .add_event::<SpawnPlayers>();
+
+    // Kept by `BrainPlugin`, which starts an empty one otherwise.
+    if let Some(fitness_history) = fitness_history {
+        app.insert_resource(fitness_history);
+    }
+
+    if headless {
+        // No window, renderer or assets: players and pipes only carry a transform.
+        app.insert_resource(SimulationSpeed::Unlimited)
+            .add_plugins(MinimalPlugins)
+            .add_plugin(InputPlugin);
+    } else {
+        app.insert_resource(ClearColor(Color::rgb(0.34, 0.75, 0.79)))
+            .insert_resource(ImageSettings::default_nearest())
+            .insert_resource(WindowDescriptor {
+                title: "Flappy Rust".to_string(),
+                width,
+                height,
+                present_mode: PresentMode::AutoVsync,
+                ..Default::default()
+            })
+            .add_plugins(DefaultPlugins)
+            .add_startup_system(setup_system)
+            .add_system(win_size_refresh_system)
+            .add_plugin(TextDisplayPlugin)
+            .add_plugin(VisualizerPlugin);
+
+        #[cfg(feature = "debug")]
+        app.add_plugin(debug::DebugPlugin);
+    }
+
+    app.add_plugin(ConfigPlugin)
+        .add_plugin(GameStatePlugin)
+        .add_plugin(SimulationPlugin)
         .add_plugin(PlayerPlugin)
         .add_plugin(GravityPlugin)
-        .add_plugin(TextDisplayPlugin)
         .add_plugin(PipePlugin)
         .add_plugin(CollisionPlugin)
-        .run();
+        .add_plugin(HighScorePlugin);
+
+    if !matches!(app.world.resource::<RunMode>(), RunMode::Train { .. }) {
+        app.add_plugin(EpisodePlugin);
+    }
+
+    app.run();
+}
+
+fn load_neural_network(path: &Path, brain: &BrainConfig) -> NeuralNetwork {
+    let neural_network = fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|data| {
+            serde_json::from_str::<NeuralNetwork>(&data).map_err(|error| error.to_string())
+        })
+        .unwrap_or_else(|error| {
+            eprintln!("Unable to load {}: {}", path.display(), error);
+            process::exit(1);
+        });
+    check_neural_network(brain, &neural_network, path);
+    neural_network
+}
+
+// Refused up front, the first feed forward would panic otherwise.
+fn check_neural_network(brain: &BrainConfig, neural_network: &NeuralNetwork, path: &Path) {
+    if let Err(error) = brain.check(neural_network) {
+        eprintln!("Unable to use {}: {}", path.display(), error);
+        process::exit(1);
+    }
 }
 
 fn setup_system(
     mut commands: Commands,
-    mut windows: ResMut<Windows>,
+    config: Res<GameConfig>,
     asset_server: Res<AssetServer>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
@@ -112,21 +454,10 @@ fn setup_system(
 
     commands.spawn_bundle(Camera2dBundle::default());
 
-    //capture window size
-
-    let window = windows.get_primary_mut().unwrap();
-
-    let (win_w, win_h) = (window.width(), window.height());
-
-    // add WinSize ressource
-
-    let win_size = WinSize { w: win_w, h: win_h };
-
-    commands.insert_resource(win_size);
     let player_texture = asset_server.load(PLAYER_SPRITE);
     let player_texture_atlas = TextureAtlas::from_grid(
         player_texture,
-        Vec2::new(PLAYER_SIZE.0, PLAYER_SIZE.1),
+        Vec2::new(config.player_size.0, config.player_size.1),
         2,
         2,
     );
@@ -138,7 +469,7 @@ fn setup_system(
     };
     let pipe_material_handle = materials.add(pipe_color_material);
 
-    let mybox = Box::new(PIPE_SIZE.0, PIPE_SIZE.1, 1.);
+    let mybox = Box::new(config.pipe_size.0, config.pipe_size.1, 1.);
 
     let mesh = Mesh::from(mybox);
 
@@ -0,0 +1,50 @@
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::Inspectable;
+use serde::{Deserialize, Serialize};
+
+const LEAKY_RELU_SLOPE: f32 = 0.01;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+pub enum Activation {
+    #[default]
+    Step,
+    Sigmoid,
+    Tanh,
+    Relu,
+    LeakyRelu,
+    Linear,
+}
+
+impl Activation {
+    // `x` is the weighted sum minus the neuron bias, so `Step` keeps the
+    // historical "sum beats the bias" behaviour.
+    pub fn activate(&self, x: f32) -> f32 {
+        match self {
+            Activation::Step => {
+                if x > 0. {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            Activation::Sigmoid => 1. / (1. + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::Relu => x.max(0.),
+            Activation::LeakyRelu => {
+                if x > 0. {
+                    x
+                } else {
+                    LEAKY_RELU_SLOPE * x
+                }
+            }
+            Activation::Linear => x,
+        }
+    }
+
+    // Output for a sum equal to the bias. Every activation is non-decreasing,
+    // so an output above it means the sum beat the bias.
+    pub fn threshold(&self) -> f32 {
+        self.activate(0.)
+    }
+}
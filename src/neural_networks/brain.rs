@@ -1,25 +1,61 @@
-use super::level::Level;
+use super::{
+    activation::Activation,
+    crossover::{CrossoverError, CrossoverMethod},
+    level::Level,
+};
 use bevy::prelude::Component;
+#[cfg(feature = "debug")]
 use bevy_inspector_egui::Inspectable;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-#[derive(Component, Inspectable, Default, Clone, Serialize, Deserialize)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 pub struct NeuralNetwork {
-    #[inspectable()]
     pub levels: Vec<Level>,
 }
 
 impl NeuralNetwork {
-    pub fn new(neurons_count: Vec<usize>) -> NeuralNetwork {
+    // One activation per level, e.g. `[Tanh, Sigmoid]` for a `[3, 6, 1]` network.
+    pub fn new<R: Rng + ?Sized>(
+        neurons_count: Vec<usize>,
+        activations: Vec<Activation>,
+        rng: &mut R,
+    ) -> NeuralNetwork {
+        assert_eq!(
+            activations.len(),
+            neurons_count.len() - 1,
+            "Expected one activation per level."
+        );
+
         let mut levels = Vec::new();
 
         for i in 0..neurons_count.len() - 1 {
-            levels.push(Level::new(neurons_count[i], neurons_count[i + 1]));
+            levels.push(Level::new(
+                neurons_count[i],
+                neurons_count[i + 1],
+                activations[i],
+                rng,
+            ));
         }
 
         NeuralNetwork { levels }
     }
 
+    pub fn input_count(&self) -> usize {
+        self.levels.first().map_or(0, |level| level.shape().0)
+    }
+
+    pub fn output_count(&self) -> usize {
+        self.levels.last().map_or(0, |level| level.shape().1)
+    }
+
+    pub fn output_activation(&self) -> Activation {
+        self.levels
+            .last()
+            .map_or_else(Activation::default, Level::activation)
+    }
+
     pub fn feed_forward(&mut self, given_inputs: Vec<f32>) -> Vec<f32> {
         let mut outputs = self.levels[0].feed_forward(given_inputs);
 
@@ -30,9 +66,86 @@ impl NeuralNetwork {
         outputs
     }
 
-    pub fn mutate(&mut self, amount: f32) {
+    pub fn crossover<R: Rng + ?Sized>(
+        &self,
+        other: &NeuralNetwork,
+        method: CrossoverMethod,
+        rng: &mut R,
+    ) -> Result<NeuralNetwork, CrossoverError> {
+        if self.levels.len() != other.levels.len() {
+            return Err(CrossoverError::LevelCountMismatch {
+                left: self.levels.len(),
+                right: other.levels.len(),
+            });
+        }
+
+        let levels = self
+            .levels
+            .iter()
+            .zip(other.levels.iter())
+            .enumerate()
+            .map(|(i, (level, other_level))| {
+                level
+                    .crossover(other_level, method, rng)
+                    .map_err(|error| error.at_level(i))
+            })
+            .collect::<Result<Vec<Level>, CrossoverError>>()?;
+
+        Ok(NeuralNetwork { levels })
+    }
+
+    pub fn mutate<R: Rng + ?Sized>(&mut self, amount: f32, rng: &mut R) {
         self.levels
             .iter_mut()
-            .for_each(|level| level.mutate(amount));
+            .for_each(|level| level.mutate(amount, rng));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    #[test]
+    fn crossover_refuses_other_level_count() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let left = NeuralNetwork::new(vec![3, 1], vec![Activation::Step], &mut rng);
+        let right = NeuralNetwork::new(
+            vec![3, 4, 1],
+            vec![Activation::Step, Activation::Step],
+            &mut rng,
+        );
+
+        let error = left
+            .crossover(&right, CrossoverMethod::Uniform, &mut rng)
+            .err();
+
+        assert_eq!(
+            error,
+            Some(CrossoverError::LevelCountMismatch { left: 1, right: 2 })
+        );
+    }
+
+    #[test]
+    fn crossover_reports_mismatched_level() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let activations = vec![Activation::Step, Activation::Step];
+        let left = NeuralNetwork::new(vec![3, 4, 1], activations.clone(), &mut rng);
+        let right = NeuralNetwork::new(vec![3, 5, 1], activations, &mut rng);
+
+        let error = left
+            .crossover(&right, CrossoverMethod::Blend, &mut rng)
+            .err();
+
+        assert_eq!(
+            error,
+            Some(CrossoverError::LevelShapeMismatch {
+                level: 0,
+                left: (3, 4),
+                right: (3, 5),
+            })
+        );
     }
 }
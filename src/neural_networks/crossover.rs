@@ -0,0 +1,58 @@
+use std::{error::Error, fmt};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, ValueEnum)]
+pub enum CrossoverMethod {
+    // Every weight and bias is picked from either parent with equal odds.
+    Uniform,
+    // Each level is cut at one random gene, the child takes the first parent's
+    // genes before the cut and the second parent's after it.
+    SinglePoint,
+    // Each level is a random weighted average of both parents.
+    Blend,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CrossoverError {
+    LevelCountMismatch {
+        left: usize,
+        right: usize,
+    },
+    LevelShapeMismatch {
+        level: usize,
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+}
+
+impl CrossoverError {
+    pub fn at_level(self, level: usize) -> CrossoverError {
+        match self {
+            CrossoverError::LevelShapeMismatch { left, right, .. } => {
+                CrossoverError::LevelShapeMismatch { level, left, right }
+            }
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for CrossoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrossoverError::LevelCountMismatch { left, right } => write!(
+                f,
+                "cannot cross networks with {} and {} levels",
+                left, right
+            ),
+            CrossoverError::LevelShapeMismatch { level, left, right } => write!(
+                f,
+                "cannot cross level {}: {}x{} does not match {}x{}",
+                level, left.0, left.1, right.0, right.1
+            ),
+        }
+    }
+}
+
+impl Error for CrossoverError {}
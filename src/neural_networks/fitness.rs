@@ -0,0 +1,46 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component, Default, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct FitnessRecord {
+    pub frames_survived: u32,
+    pub pipes_passed: u32,
+    // Vertical distance to the next gap center at death, in half window heights.
+    pub gap_distance: f32,
+}
+
+pub trait Fitness: Send + Sync {
+    fn evaluate(&self, record: &FitnessRecord) -> f32;
+}
+
+pub struct WeightedFitness {
+    pub frame_weight: f32,
+    pub pipe_weight: f32,
+    pub gap_distance_weight: f32,
+}
+
+impl Default for WeightedFitness {
+    fn default() -> Self {
+        WeightedFitness {
+            frame_weight: 1.,
+            pipe_weight: 200.,
+            gap_distance_weight: 100.,
+        }
+    }
+}
+
+impl Fitness for WeightedFitness {
+    fn evaluate(&self, record: &FitnessRecord) -> f32 {
+        record.frames_survived as f32 * self.frame_weight
+            + record.pipes_passed as f32 * self.pipe_weight
+            - record.gap_distance * self.gap_distance_weight
+    }
+}
+
+pub struct FitnessFunction(pub Box<dyn Fitness>);
+
+impl Default for FitnessFunction {
+    fn default() -> Self {
+        FitnessFunction(Box::new(WeightedFitness::default()))
+    }
+}
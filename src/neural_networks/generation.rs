@@ -1,8 +1,48 @@
-use super::brain::NeuralNetwork;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    brain::NeuralNetwork,
+    crossover::{CrossoverError, CrossoverMethod},
+    selection::{Selection, SelectionMethod, Truncation},
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EvolutionSettings {
+    pub population_size: usize,
+    // Fittest networks copied unmutated into the next generation.
+    pub elites: usize,
+    pub mutation_amount: f32,
+    pub crossover: CrossoverMethod,
+    pub selection: SelectionMethod,
+}
+
+impl Default for EvolutionSettings {
+    fn default() -> Self {
+        EvolutionSettings {
+            population_size: 500,
+            elites: 1,
+            mutation_amount: 0.10,
+            crossover: CrossoverMethod::Uniform,
+            selection: SelectionMethod::Truncation(Truncation { top_k: 10 }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct GenerationFitness {
+    pub best: f32,
+    pub mean: f32,
+}
+
+// Fitness of every finished generation since the app started, oldest first.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct FitnessHistory(pub Vec<GenerationFitness>);
 
 #[derive(Clone)]
 pub struct Generation {
-    pub neural_networks: Vec<NeuralNetwork>,
+    // Sorted by fitness, best first.
+    pub neural_networks: Vec<(NeuralNetwork, f32)>,
     pub generation_number: u32,
 }
 
@@ -13,4 +53,101 @@ impl Generation {
             generation_number: 0,
         }
     }
+
+    // Equal fitnesses keep their insertion order.
+    pub fn add(&mut self, neural_network: NeuralNetwork, fitness: f32) {
+        let index = self
+            .neural_networks
+            .partition_point(|(_, other_fitness)| *other_fitness >= fitness);
+        self.neural_networks
+            .insert(index, (neural_network, fitness));
+    }
+
+    pub fn best(&self) -> Option<&NeuralNetwork> {
+        self.neural_networks
+            .first()
+            .map(|(neural_network, _)| neural_network)
+    }
+
+    pub fn fitness(&self) -> Option<GenerationFitness> {
+        let best = self.neural_networks.first()?.1;
+        let mean = self
+            .neural_networks
+            .iter()
+            .map(|(_, fitness)| fitness)
+            .sum::<f32>()
+            / self.neural_networks.len() as f32;
+
+        Some(GenerationFitness { best, mean })
+    }
+
+    // Elites first, then children of two selected parents, crossed and mutated.
+    // Fails when two parents do not have the same shape.
+    pub fn breed(
+        &self,
+        settings: &EvolutionSettings,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<NeuralNetwork>, CrossoverError> {
+        let mut next_population: Vec<NeuralNetwork> = self
+            .neural_networks
+            .iter()
+            .take(settings.elites.min(settings.population_size))
+            .map(|(neural_network, _)| neural_network.clone())
+            .collect();
+
+        while next_population.len() < settings.population_size {
+            let first = settings.selection.select(&self.neural_networks, rng);
+            let second = settings.selection.select(&self.neural_networks, rng);
+
+            let (first, second) = match (first, second) {
+                (Some(first), Some(second)) => (first, second),
+                _ => break,
+            };
+
+            let mut child = first.crossover(second, settings.crossover, rng)?;
+            child.mutate(settings.mutation_amount, rng);
+            next_population.push(child);
+        }
+
+        Ok(next_population)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::super::activation::Activation;
+    use super::*;
+
+    #[test]
+    fn breed_copies_elites_first() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut generation = Generation::new();
+        for fitness in [1., 3., 2.] {
+            let neural_network = NeuralNetwork::new(vec![2, 1], vec![Activation::Step], &mut rng);
+            generation.add(neural_network, fitness);
+        }
+        let settings = EvolutionSettings {
+            population_size: 5,
+            elites: 2,
+            ..EvolutionSettings::default()
+        };
+
+        let next_population = generation.breed(&settings, &mut rng).unwrap();
+
+        assert_eq!(next_population.len(), 5);
+        for (elite, (neural_network, _)) in next_population
+            .iter()
+            .zip(generation.neural_networks.iter())
+            .take(2)
+        {
+            assert_eq!(
+                elite.levels[0].weights(),
+                neural_network.levels[0].weights()
+            );
+            assert_eq!(elite.levels[0].biases(), neural_network.levels[0].biases());
+        }
+    }
 }
@@ -1,35 +1,144 @@
+#[cfg(feature = "debug")]
 use bevy_inspector_egui::Inspectable;
-use rand::random;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-#[derive(Inspectable, Default, Clone, Serialize, Deserialize)]
+use super::{
+    activation::Activation,
+    crossover::{CrossoverError, CrossoverMethod},
+};
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 pub struct Level {
     inputs: Vec<f32>,
     outputs: Vec<f32>,
     biases: Vec<f32>,
     weights: Vec<Vec<f32>>,
+    #[serde(default)]
+    activation: Activation,
 }
 
 impl Level {
-    pub fn new(input_count: usize, output_count: usize) -> Level {
+    pub fn new<R: Rng + ?Sized>(
+        input_count: usize,
+        output_count: usize,
+        activation: Activation,
+        rng: &mut R,
+    ) -> Level {
         let mut level = Level {
             biases: vec![0.; output_count],
             inputs: vec![0.; input_count],
             outputs: vec![0.; output_count],
             weights: vec![vec![0.; output_count]; input_count],
+            activation,
         };
-        level.randomize_level();
+        level.randomize_level(rng);
         level
     }
 
-    fn randomize_level(&mut self) {
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.inputs.len(), self.outputs.len())
+    }
+
+    // Inputs and outputs of the last `feed_forward`.
+    pub fn inputs(&self) -> &[f32] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[f32] {
+        &self.outputs
+    }
+
+    pub fn biases(&self) -> &[f32] {
+        &self.biases
+    }
+
+    // Indexed by input then output.
+    pub fn weights(&self) -> &[Vec<f32>] {
+        &self.weights
+    }
+
+    pub fn crossover<R: Rng + ?Sized>(
+        &self,
+        other: &Level,
+        method: CrossoverMethod,
+        rng: &mut R,
+    ) -> Result<Level, CrossoverError> {
+        if self.shape() != other.shape() {
+            return Err(CrossoverError::LevelShapeMismatch {
+                level: 0,
+                left: self.shape(),
+                right: other.shape(),
+            });
+        }
+
+        let parent_genes = self.genes();
+        let other_genes = other.genes();
+
+        let child_genes: Vec<f32> = match method {
+            CrossoverMethod::Uniform => parent_genes
+                .iter()
+                .zip(other_genes.iter())
+                .map(|(a, b)| if rng.gen::<bool>() { *a } else { *b })
+                .collect(),
+            CrossoverMethod::SinglePoint => {
+                let cut = rng.gen_range(0..=parent_genes.len());
+                parent_genes[..cut]
+                    .iter()
+                    .chain(other_genes[cut..].iter())
+                    .copied()
+                    .collect()
+            }
+            CrossoverMethod::Blend => {
+                let t = rng.gen::<f32>();
+                parent_genes
+                    .iter()
+                    .zip(other_genes.iter())
+                    .map(|(a, b)| lerp(*a, *b, t))
+                    .collect()
+            }
+        };
+
+        let mut child = self.clone();
+        child.set_genes(&child_genes);
+        Ok(child)
+    }
+
+    // Weights row by row, followed by the biases.
+    fn genes(&self) -> Vec<f32> {
+        self.weights
+            .iter()
+            .flatten()
+            .chain(self.biases.iter())
+            .copied()
+            .collect()
+    }
+
+    fn set_genes(&mut self, genes: &[f32]) {
+        let mut genes = genes.iter().copied();
+        for weight_vec in self.weights.iter_mut() {
+            for weight in weight_vec.iter_mut() {
+                *weight = genes.next().unwrap();
+            }
+        }
+        for bias in self.biases.iter_mut() {
+            *bias = genes.next().unwrap();
+        }
+    }
+
+    fn randomize_level<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         self.weights = self
             .weights
             .iter()
             .map(|weight_vec| {
                 weight_vec
                     .iter()
-                    .map(|_weight| random::<f32>() * 2. - 1.)
+                    .map(|_weight| rng.gen::<f32>() * 2. - 1.)
                     .collect()
             })
             .collect();
@@ -37,7 +146,7 @@ impl Level {
         self.biases = self
             .biases
             .iter()
-            .map(|_bias| random::<f32>() * 2. - 1.)
+            .map(|_bias| rng.gen::<f32>() * 2. - 1.)
             .collect();
     }
 
@@ -49,24 +158,20 @@ impl Level {
                 sum += self.inputs[j] * self.weights[j][i];
             }
 
-            if sum > self.biases[i] {
-                self.outputs[i] = 1.;
-            } else {
-                self.outputs[i] = 0.;
-            }
+            self.outputs[i] = self.activation.activate(sum - self.biases[i]);
         }
 
         self.outputs.clone()
     }
 
-    pub fn mutate(&mut self, amount: f32) {
+    pub fn mutate<R: Rng + ?Sized>(&mut self, amount: f32, rng: &mut R) {
         self.weights = self
             .weights
             .iter()
             .map(|weight| {
                 weight
                     .iter()
-                    .map(|w| lerp(*w, random::<f32>() * 2. - 1., amount))
+                    .map(|w| lerp(*w, rng.gen::<f32>() * 2. - 1., amount))
                     .collect::<Vec<f32>>()
             })
             .collect();
@@ -74,7 +179,7 @@ impl Level {
         self.biases = self
             .biases
             .iter()
-            .map(|bias| lerp(*bias, random::<f32>() * 2. - 1., amount))
+            .map(|bias| lerp(*bias, rng.gen::<f32>() * 2. - 1., amount))
             .collect();
     }
 }
@@ -82,3 +187,73 @@ impl Level {
 fn lerp(v0: f32, v1: f32, t: f32) -> f32 {
     (1. - t) * v0 + t * v1
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    #[test]
+    fn genes_round_trip() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let level = Level::new(3, 2, Activation::Step, &mut rng);
+        let mut copy = Level::new(3, 2, Activation::Step, &mut rng);
+
+        copy.set_genes(&level.genes());
+
+        assert_eq!(copy.weights(), level.weights());
+        assert_eq!(copy.biases(), level.biases());
+    }
+
+    #[test]
+    fn uniform_crossover_takes_each_gene_from_a_parent() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let left = Level::new(3, 2, Activation::Step, &mut rng);
+        let right = Level::new(3, 2, Activation::Step, &mut rng);
+
+        let child = left
+            .crossover(&right, CrossoverMethod::Uniform, &mut rng)
+            .unwrap();
+
+        for ((gene, left_gene), right_gene) in child
+            .genes()
+            .iter()
+            .zip(left.genes().iter())
+            .zip(right.genes().iter())
+        {
+            assert!(gene == left_gene || gene == right_gene);
+        }
+    }
+
+    #[test]
+    fn crossover_refuses_other_shape() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let left = Level::new(3, 2, Activation::Step, &mut rng);
+        let right = Level::new(2, 2, Activation::Step, &mut rng);
+
+        let error = left
+            .crossover(&right, CrossoverMethod::SinglePoint, &mut rng)
+            .err();
+
+        assert_eq!(
+            error,
+            Some(CrossoverError::LevelShapeMismatch {
+                level: 0,
+                left: (3, 2),
+                right: (2, 2),
+            })
+        );
+    }
+
+    #[test]
+    fn save_without_activation_loads_as_step() {
+        let level: Level = serde_json::from_str(
+            r#"{"inputs":[0.0],"outputs":[0.0],"biases":[0.5],"weights":[[1.0]]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(level.activation(), Activation::Step);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod activation;
 pub mod brain;
+pub mod crossover;
+pub mod fitness;
 pub mod generation;
 pub mod level;
+pub mod selection;
@@ -0,0 +1,255 @@
+use std::str::FromStr;
+
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use super::brain::NeuralNetwork;
+
+// Picks one parent out of a population sorted by fitness, best first.
+pub trait Selection: Send + Sync {
+    fn select<'a>(
+        &self,
+        population: &'a [(NeuralNetwork, f32)],
+        rng: &mut dyn RngCore,
+    ) -> Option<&'a NeuralNetwork>;
+}
+
+// Serializable choice of strategy, so training settings can be saved with a checkpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SelectionMethod {
+    Truncation(Truncation),
+    Tournament(Tournament),
+    Roulette(Roulette),
+}
+
+impl Selection for SelectionMethod {
+    fn select<'a>(
+        &self,
+        population: &'a [(NeuralNetwork, f32)],
+        rng: &mut dyn RngCore,
+    ) -> Option<&'a NeuralNetwork> {
+        match self {
+            SelectionMethod::Truncation(truncation) => truncation.select(population, rng),
+            SelectionMethod::Tournament(tournament) => tournament.select(population, rng),
+            SelectionMethod::Roulette(roulette) => roulette.select(population, rng),
+        }
+    }
+}
+
+// Written `truncation:K`, `tournament:K` or `roulette` on the command line.
+impl FromStr for SelectionMethod {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, size) = match value.split_once(':') {
+            Some((name, size)) => (name, Some(size)),
+            None => (value, None),
+        };
+        let size = size
+            .map(|size| {
+                size.parse::<usize>()
+                    .ok()
+                    .filter(|size| *size > 0)
+                    .ok_or_else(|| format!("`{}` is not a size greater than 0", size))
+            })
+            .transpose()?;
+
+        match (name, size) {
+            ("truncation", Some(top_k)) => Ok(SelectionMethod::Truncation(Truncation { top_k })),
+            ("tournament", Some(size)) => Ok(SelectionMethod::Tournament(Tournament { size })),
+            ("roulette", None) => Ok(SelectionMethod::Roulette(Roulette)),
+            ("truncation" | "tournament", None) => {
+                Err(format!("`{}` needs a size, e.g. `{}:5`", name, name))
+            }
+            ("roulette", Some(_)) => Err("`roulette` takes no size".to_string()),
+            _ => Err(format!(
+                "unknown selection `{}`, expected truncation:K, tournament:K or roulette",
+                name
+            )),
+        }
+    }
+}
+
+// Uniform pick among the `top_k` fittest networks.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Truncation {
+    pub top_k: usize,
+}
+
+impl Selection for Truncation {
+    fn select<'a>(
+        &self,
+        population: &'a [(NeuralNetwork, f32)],
+        rng: &mut dyn RngCore,
+    ) -> Option<&'a NeuralNetwork> {
+        let top_k = self.top_k.clamp(1, population.len().max(1));
+        population
+            .get(rng.gen_range(0..top_k))
+            .map(|(neural_network, _)| neural_network)
+    }
+}
+
+// Best of `size` networks drawn at random.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl Selection for Tournament {
+    fn select<'a>(
+        &self,
+        population: &'a [(NeuralNetwork, f32)],
+        rng: &mut dyn RngCore,
+    ) -> Option<&'a NeuralNetwork> {
+        if population.is_empty() {
+            return None;
+        }
+
+        // The population is sorted, so the lowest drawn index wins.
+        let winner = (0..self.size.max(1))
+            .map(|_| rng.gen_range(0..population.len()))
+            .min()?;
+        Some(&population[winner].0)
+    }
+}
+
+// Fitness proportionate pick, fitnesses are shifted so the worst network has no chance.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Roulette;
+
+impl Selection for Roulette {
+    fn select<'a>(
+        &self,
+        population: &'a [(NeuralNetwork, f32)],
+        rng: &mut dyn RngCore,
+    ) -> Option<&'a NeuralNetwork> {
+        let worst = population.last()?.1;
+        let total: f32 = population.iter().map(|(_, fitness)| fitness - worst).sum();
+
+        if total <= 0. {
+            return Some(&population[rng.gen_range(0..population.len())].0);
+        }
+
+        let mut pick = rng.gen::<f32>() * total;
+        for (neural_network, fitness) in population {
+            pick -= fitness - worst;
+            if pick <= 0. {
+                return Some(neural_network);
+            }
+        }
+
+        population.first().map(|(neural_network, _)| neural_network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::super::activation::Activation;
+    use super::*;
+
+    fn population(fitnesses: &[f32], rng: &mut ChaCha8Rng) -> Vec<(NeuralNetwork, f32)> {
+        fitnesses
+            .iter()
+            .map(|fitness| {
+                let neural_network = NeuralNetwork::new(vec![2, 1], vec![Activation::Step], rng);
+                (neural_network, *fitness)
+            })
+            .collect()
+    }
+
+    fn index_of(population: &[(NeuralNetwork, f32)], picked: &NeuralNetwork) -> usize {
+        population
+            .iter()
+            .position(|(neural_network, _)| std::ptr::eq(neural_network, picked))
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_every_method() {
+        assert_eq!(
+            "truncation:3".parse(),
+            Ok(SelectionMethod::Truncation(Truncation { top_k: 3 }))
+        );
+        assert_eq!(
+            "tournament:4".parse(),
+            Ok(SelectionMethod::Tournament(Tournament { size: 4 }))
+        );
+        assert_eq!("roulette".parse(), Ok(SelectionMethod::Roulette(Roulette)));
+    }
+
+    #[test]
+    fn reports_malformed_methods() {
+        let error = |value: &str| value.parse::<SelectionMethod>().err();
+
+        assert_eq!(
+            error("tournament:0"),
+            Some("`0` is not a size greater than 0".to_string())
+        );
+        assert_eq!(
+            error("truncation:many"),
+            Some("`many` is not a size greater than 0".to_string())
+        );
+        assert_eq!(
+            error("truncation"),
+            Some("`truncation` needs a size, e.g. `truncation:5`".to_string())
+        );
+        assert_eq!(
+            error("roulette:2"),
+            Some("`roulette` takes no size".to_string())
+        );
+        assert_eq!(
+            error("best:2"),
+            Some(
+                "unknown selection `best`, expected truncation:K, tournament:K or roulette"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn roulette_never_picks_the_worst() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let population = population(&[-1., -2., -5.], &mut rng);
+
+        for _ in 0..100 {
+            let picked = Roulette.select(&population, &mut rng).unwrap();
+            assert_ne!(index_of(&population, picked), 2);
+        }
+    }
+
+    #[test]
+    fn roulette_picks_uniformly_among_equal_fitnesses() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let population = population(&[1., 1., 1.], &mut rng);
+        let mut picks = [0; 3];
+
+        for _ in 0..100 {
+            let picked = Roulette.select(&population, &mut rng).unwrap();
+            picks[index_of(&population, picked)] += 1;
+        }
+
+        assert!(picks.iter().all(|count| *count > 0));
+    }
+
+    #[test]
+    fn tournament_picks_the_lowest_drawn_index() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let population = population(&[5., 4., 3., 2., 1.], &mut rng);
+        let tournament = Tournament { size: 3 };
+
+        for _ in 0..20 {
+            let mut draws = rng.clone();
+            let lowest = (0..tournament.size)
+                .map(|_| draws.gen_range(0..population.len()))
+                .min()
+                .unwrap();
+
+            let picked = tournament.select(&population, &mut rng).unwrap();
+
+            assert_eq!(index_of(&population, picked), lowest);
+        }
+    }
+}
@@ -2,98 +2,312 @@ use bevy::{
     prelude::*,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
 };
-use rand::{thread_rng, Rng};
 
 use crate::{
-    components::{Collider, Pipe, Velocity},
-    GameTextures, PipeSpawnSettings, WinSize, BASE_SPEED, PIPE_SIZE, PIPE_SPRITE_SCALE,
+    components::{Collider, Interpolated, Obstacle, Pipe, Velocity},
+    config::GameConfig,
+    course::{Motion, ObstacleMotion},
+    player::{
+        components::{Player, Score},
+        events::PlayerDieEvent,
+    },
+    rng::GameRng,
+    simulation::{SimulationLabel, SimulationStage, FIXED_TIMESTEP},
+    GameTextures, PipeSpawnSettings, WinSize,
 };
 
 pub struct PipePlugin;
 
+// Opening of an obstacle a bird still has to fly through.
+pub struct UpcomingGap {
+    pub x: f32,
+    pub center: f32,
+    // Edges of the pipes around the gap.
+    pub top: f32,
+    pub bottom: f32,
+    // Scrolling speed, as a fraction of the base speed.
+    pub speed: f32,
+}
+
+// Gaps the bird at `bird_x` is not fully past yet, nearest first.
+pub fn upcoming_gaps<'a>(
+    config: &GameConfig,
+    bird_x: f32,
+    obstacles: impl Iterator<Item = (&'a Transform, &'a Obstacle, &'a Velocity)>,
+) -> Vec<UpcomingGap> {
+    let reach = bird_x - config.player_extent().x / 2. - config.pipe_extent().x / 2.;
+    let mut gaps: Vec<UpcomingGap> = obstacles
+        .filter(|(transform, _, _)| transform.translation.x >= reach)
+        // Moving obstacles carry their gap along.
+        .map(|(transform, obstacle, velocity)| UpcomingGap {
+            x: transform.translation.x,
+            center: transform.translation.y + obstacle.gap_center,
+            top: transform.translation.y + obstacle.top(),
+            bottom: transform.translation.y + obstacle.bottom(),
+            speed: -velocity.x,
+        })
+        .collect();
+    gaps.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+    gaps
+}
+
+// Pipes and triggers only hold their offset in the obstacle, which is never
+// rotated nor scaled. Global transforms lag behind during simulation steps.
+pub fn part_translation(obstacle_transform: &Transform, part_transform: &Transform) -> Vec3 {
+    obstacle_transform.translation + part_transform.translation
+}
+
 impl Plugin for PipePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(pipe_movement_system)
-            .add_system(pipe_spawn_system)
-            .add_system(pipe_despawn_system);
+        app.add_system_to_stage(
+            SimulationStage,
+            pipe_spawn_system.label(SimulationLabel::Decision),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            course_spawn_system.label(SimulationLabel::Decision),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            pipe_movement_system
+                .label(SimulationLabel::Movement)
+                .after(SimulationLabel::Physics),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            obstacle_motion_system
+                .label(SimulationLabel::Movement)
+                .after(SimulationLabel::Physics),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            pipe_despawn_system
+                .label(SimulationLabel::Collision)
+                .after(SimulationLabel::Movement),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            course_complete_system
+                .label(SimulationLabel::Collision)
+                .after(SimulationLabel::Movement),
+        );
     }
 }
 
 fn pipe_despawn_system(
     mut commands: Commands,
+    config: Res<GameConfig>,
     win_size: Res<WinSize>,
-    query: Query<(&Transform, Entity), With<Collider>>,
+    query: Query<(&Transform, Entity), With<Obstacle>>,
 ) {
     for (transform, entity) in query.iter() {
         let translation = &transform.translation;
-        if translation.x < (-win_size.w / 2. - PIPE_SIZE.0 * PIPE_SPRITE_SCALE) {
-            commands.entity(entity).despawn();
+        if translation.x < (-win_size.w / 2. - config.pipe_extent().x) {
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
 
+// The whole course scrolls at the speed picked by the level generator, or at
+// the config speed for a course file.
 fn pipe_movement_system(
-    time: Res<Time>,
-    mut query: Query<(&Velocity, &mut Transform), With<Collider>>,
+    config: Res<GameConfig>,
+    mut pipe_spawn_settings: ResMut<PipeSpawnSettings>,
+    mut query: Query<(&mut Velocity, &mut Transform), With<Obstacle>>,
 ) {
-    for (velocity, mut transform) in query.iter_mut() {
+    let scroll_velocity = pipe_spawn_settings.scroll_velocity(&config);
+    let distance = -scroll_velocity * FIXED_TIMESTEP * config.base_speed;
+    pipe_spawn_settings.level.advance(distance);
+    pipe_spawn_settings.distance_left -= distance;
+
+    for (mut velocity, mut transform) in query.iter_mut() {
+        velocity.x = scroll_velocity;
         let translation = &mut transform.translation;
-        let delta = time.delta_seconds();
-        translation.x += velocity.x * delta * BASE_SPEED;
+        translation.x += velocity.x * FIXED_TIMESTEP * config.base_speed;
+    }
+}
+
+// Moving gaps take their pipes and trigger along, those being its children.
+fn obstacle_motion_system(mut query: Query<(&mut ObstacleMotion, &mut Transform), With<Obstacle>>) {
+    for (mut obstacle_motion, mut transform) in query.iter_mut() {
+        obstacle_motion.elapsed += FIXED_TIMESTEP;
+        transform.translation.y = obstacle_motion.motion.offset(obstacle_motion.elapsed);
     }
 }
 
+// Spawning on scrolled distance keeps the spacing whatever the speed and time scale.
+#[allow(clippy::too_many_arguments)]
 fn pipe_spawn_system(
     mut commands: Commands,
-    time: Res<Time>,
+    config: Res<GameConfig>,
+    win_size: Res<WinSize>,
     mut pipe_spawn_settings: ResMut<PipeSpawnSettings>,
-    game_textures: Res<GameTextures>,
+    mut game_rng: ResMut<GameRng>,
+    mut next_id: Local<u32>,
+    game_textures: Option<Res<GameTextures>>,
+    score_query: Query<&Score, With<Player>>,
 ) {
-    pipe_spawn_settings.timer.tick(time.delta());
-    if pipe_spawn_settings.timer.just_finished() {
-        let mut rng = thread_rng();
-        let random_f32: f32 = rng.gen_range(-100. ..100.);
-        spawn_pipe(&mut commands, game_textures, random_f32);
+    if pipe_spawn_settings.course.is_some() {
+        return;
+    }
+
+    if let Some(score) = score_query.iter().map(|score| score.0).max() {
+        pipe_spawn_settings.level.record_score(score);
+    }
+
+    if pipe_spawn_settings.distance_left <= 0. {
+        let next_obstacle = pipe_spawn_settings
+            .level
+            .next_obstacle(&config, &mut game_rng.pipes);
+
+        // Scrolled past the spawn point by `-distance_left`, so the obstacle
+        // is placed that much closer to keep the spacing exact.
+        let x = win_size.w / 2. + config.pipe_extent().x / 2. + pipe_spawn_settings.distance_left;
+        pipe_spawn_settings.distance_left += next_obstacle.spacing;
+
+        let obstacle = Obstacle {
+            id: *next_id,
+            gap_center: next_obstacle.gap_center,
+            gap_size: next_obstacle.gap_size,
+        };
+        *next_id += 1;
+        spawn_obstacle(
+            &mut commands,
+            &config,
+            game_textures.as_deref(),
+            obstacle,
+            x,
+            pipe_spawn_settings.level.scroll_velocity(&config),
+            None,
+        );
     }
 }
 
-fn spawn_pipe(commands: &mut Commands, game_textures: Res<GameTextures>, random_f32: f32) {
-    commands
-        .spawn_bundle(MaterialMesh2dBundle {
-            mesh: Mesh2dHandle(game_textures.pipe_mesh.clone()),
-            material: game_textures.pipe_material.clone(),
-            transform: Transform {
-                scale: Vec3::new(PIPE_SPRITE_SCALE, PIPE_SPRITE_SCALE, 0.0),
-                translation: Vec3::new(1200., 300. + random_f32, 3.),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Collider::Loss)
-        .insert(Pipe)
-        .insert(Velocity { x: -0.5, y: 0. });
+// Obstacles of the course file, in order, once the course has scrolled by
+// their spacing.
+fn course_spawn_system(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    win_size: Res<WinSize>,
+    mut pipe_spawn_settings: ResMut<PipeSpawnSettings>,
+    mut next_id: Local<u32>,
+    game_textures: Option<Res<GameTextures>>,
+) {
+    let distance = pipe_spawn_settings.level.distance();
+    let course = match &mut pipe_spawn_settings.course {
+        Some(course) => course,
+        None => return,
+    };
+
+    // Placed closer by the distance scrolled past its spawn point, like
+    // generated obstacles.
+    while let Some((course_obstacle, overshoot)) = course.next_obstacle(distance) {
+        let x = win_size.w / 2. + config.pipe_extent().x / 2. - overshoot;
+
+        let obstacle = Obstacle {
+            id: *next_id,
+            gap_center: course_obstacle.gap_center,
+            gap_size: course_obstacle.gap_size,
+        };
+        *next_id += 1;
+        spawn_obstacle(
+            &mut commands,
+            &config,
+            game_textures.as_deref(),
+            obstacle,
+            x,
+            config.pipe_velocity,
+            course_obstacle.motion,
+        );
+    }
+}
+
+// A course that does not loop ends once its last obstacle has left the
+// screen, with every bird still flying through it.
+fn course_complete_system(
+    mut pipe_spawn_settings: ResMut<PipeSpawnSettings>,
+    mut writer: EventWriter<PlayerDieEvent>,
+    obstacle_query: Query<(), With<Obstacle>>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    let course = match &mut pipe_spawn_settings.course {
+        Some(course) => course,
+        None => return,
+    };
+    if course.complete || !course.is_finished() || !obstacle_query.is_empty() {
+        return;
+    }
+
+    course.complete = true;
+    for entity in player_query.iter() {
+        writer.send(PlayerDieEvent(entity));
+    }
+}
 
-    commands
-        .spawn_bundle(MaterialMesh2dBundle {
+// One moving parent, with both pipes and the scoring trigger as children.
+fn spawn_obstacle(
+    commands: &mut Commands,
+    config: &GameConfig,
+    game_textures: Option<&GameTextures>,
+    obstacle: Obstacle,
+    x: f32,
+    velocity: f32,
+    motion: Option<Motion>,
+) {
+    let scale = Vec3::new(config.pipe_sprite_scale, config.pipe_sprite_scale, 0.0);
+    let half_pipe = config.pipe_extent().y / 2.;
+    let translation = Vec3::new(x, 0., 3.);
+
+    let top_transform = Transform {
+        scale,
+        translation: Vec3::new(0., obstacle.top() + half_pipe, 0.),
+        ..Default::default()
+    };
+    let bottom_transform = Transform {
+        scale,
+        translation: Vec3::new(0., obstacle.bottom() - half_pipe, 0.),
+        rotation: Quat::from_rotation_z(std::f32::consts::PI),
+    };
+    let trigger_transform = Transform::from_translation(Vec3::new(0., obstacle.gap_center, -3.));
+
+    let mut entity = commands.spawn_bundle(TransformBundle::from_transform(
+        Transform::from_translation(translation),
+    ));
+    if let Some(motion) = motion {
+        entity.insert(ObstacleMotion {
+            motion,
+            elapsed: 0.,
+        });
+    }
+
+    entity
+        .insert(obstacle)
+        .insert(Interpolated::new(translation))
+        .insert(Velocity { x: velocity, y: 0. })
+        .with_children(|parent| {
+            spawn_pipe_sprite(parent, game_textures, top_transform);
+            spawn_pipe_sprite(parent, game_textures, bottom_transform);
+            parent
+                .spawn_bundle(TransformBundle::from_transform(trigger_transform))
+                .insert(Collider::Win);
+        });
+}
+
+// Without textures (headless mode) the pipe only gets a transform.
+fn spawn_pipe_sprite(
+    parent: &mut ChildBuilder,
+    game_textures: Option<&GameTextures>,
+    transform: Transform,
+) {
+    let mut pipe = match game_textures {
+        Some(game_textures) => parent.spawn_bundle(MaterialMesh2dBundle {
             mesh: Mesh2dHandle(game_textures.pipe_mesh.clone()),
             material: game_textures.pipe_material.clone(),
-            transform: Transform {
-                scale: Vec3::new(PIPE_SPRITE_SCALE, PIPE_SPRITE_SCALE, 0.0),
-                translation: Vec3::new(1200., -300. + random_f32, 3.),
-                rotation: Quat::from_rotation_z(std::f32::consts::PI),
-            },
+            transform,
             ..Default::default()
-        })
-        .insert(Collider::Loss)
-        .insert(Pipe)
-        .insert(Velocity { x: -0.5, y: 0. });
+        }),
+        None => parent.spawn_bundle(TransformBundle::from_transform(transform)),
+    };
 
-    commands.spawn_bundle((
-        Transform {
-            translation: Vec3::new(1200., random_f32, 0.),
-            ..Default::default()
-        },
-        Collider::Win,
-        Velocity { x: -0.5, y: 0. },
-    ));
+    pipe.insert(Collider::Loss).insert(Pipe);
 }
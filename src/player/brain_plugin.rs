@@ -1,68 +1,174 @@
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::Path;
-
-use bevy::prelude::{
-    Commands, Entity, EventReader, EventWriter, Plugin, Query, Res, ResMut, Transform, With,
+use std::fs;
+
+use bevy::{
+    app::AppExit,
+    prelude::{
+        Commands, DespawnRecursiveExt, Entity, EventReader, EventWriter,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, Transform, With, Without,
+    },
 };
 
 use crate::{
-    components::{Collider, Pipe, Velocity},
-    neural_networks::{brain::NeuralNetwork, generation::Generation},
-    WinSize, BASE_SPEED,
+    checkpoint::{Checkpoint, CHECKPOINT_FILE},
+    components::{Obstacle, Velocity},
+    config::GameConfig,
+    neural_networks::{
+        brain::NeuralNetwork,
+        fitness::{FitnessFunction, FitnessRecord},
+        generation::{EvolutionSettings, FitnessHistory, Generation},
+    },
+    pipe::upcoming_gaps,
+    rng::GameRng,
+    simulation::{SimulationLabel, SimulationStage},
+    PipeSpawnSettings, RunMode, WinSize, NEURAL_NETWORK_SAVE_FILE,
 };
 
 use super::{
-    components::Player,
+    components::{Controller, Player, Score},
     events::{PlayerDieEvent, SpawnPlayers},
+    sensors::BirdView,
+    vision_plugin::RayHits,
 };
 
 pub struct BrainPlugin;
 
 impl Plugin for BrainPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system(player_generation_add_player_system)
-            .add_system(player_neural_network_feed_forward_system)
-            .add_system(player_mutate_on_generation_die_system);
+        app.add_system_to_stage(
+            SimulationStage,
+            player_neural_network_feed_forward_system
+                .label(SimulationLabel::Decision)
+                .after(SimulationLabel::Sensing),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            player_survival_system
+                .label(SimulationLabel::Evolution)
+                .after(SimulationLabel::Collision),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            player_generation_add_player_system
+                .label(SimulationLabel::Evolution)
+                .after(SimulationLabel::Collision)
+                // Reads the fitness recorded for the players dying this step.
+                .after(player_survival_system),
+        );
+
+        if matches!(app.world.resource::<RunMode>(), RunMode::Train { .. }) {
+            app.init_resource::<FitnessHistory>().add_system_to_stage(
+                SimulationStage,
+                player_mutate_on_generation_die_system
+                    .label(SimulationLabel::Generation)
+                    .after(SimulationLabel::Evolution),
+            );
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn player_mutate_on_generation_die_system(
+    config: Res<GameConfig>,
     mut generations: ResMut<Generation>,
+    mut fitness_history: ResMut<FitnessHistory>,
+    evolution_settings: Res<EvolutionSettings>,
+    mut game_rng: ResMut<GameRng>,
+    mut pipe_spawn_settings: ResMut<PipeSpawnSettings>,
+    run_mode: Res<RunMode>,
     query: Query<Entity, With<Player>>,
     mut writer: EventWriter<SpawnPlayers>,
-    query_collision: Query<Entity, With<Collider>>,
+    mut exit: EventWriter<AppExit>,
+    query_obstacle: Query<Entity, With<Obstacle>>,
     mut commands: Commands,
 ) {
+    let (generation_limit, output) = match &*run_mode {
+        RunMode::Train {
+            generation_limit,
+            output,
+            ..
+        } => (*generation_limit, output),
+        _ => return,
+    };
+
     if query.iter().len() == 0 {
-        if let Some(neural_network) = generations.neural_networks.last() {
-            if Path::new("neural_network_save.json").exists() {
-                fs::remove_file("neural_network_save.json").unwrap();
+        if let Some(neural_network) = generations.best() {
+            let save_file = output.join(NEURAL_NETWORK_SAVE_FILE);
+            if let Err(error) =
+                fs::write(&save_file, serde_json::to_string(neural_network).unwrap())
+            {
+                eprintln!("Unable to save {}: {}", save_file.display(), error);
             }
 
-            let mut file = File::create("neural_network_save.json").unwrap();
-
-            file.write_all(serde_json::to_string(neural_network).unwrap().as_bytes())
-                .unwrap();
+            // Saved before breeding so a resumed run breeds the exact same population.
+            let checkpoint = Checkpoint::new(
+                &generations,
+                &game_rng,
+                &evolution_settings,
+                &config,
+                &fitness_history,
+                pipe_spawn_settings.level.best_score(),
+            );
+            if let Err(error) = checkpoint.save(&output.join(CHECKPOINT_FILE)) {
+                eprintln!("Unable to save checkpoint: {}", error);
+            }
 
+            let neural_networks =
+                match generations.breed(&evolution_settings, &mut game_rng.mutation) {
+                    Ok(neural_networks) => neural_networks,
+                    Err(error) => {
+                        eprintln!(
+                            "Unable to breed generation {}: {}",
+                            generations.generation_number + 1,
+                            error
+                        );
+                        exit.send(AppExit);
+                        return;
+                    }
+                };
             writer.send(SpawnPlayers {
-                number: 500,
-                neural_network: Some(neural_network.clone()),
+                number: evolution_settings.population_size as u32,
+                neural_networks,
             });
 
+            fitness_history.0.extend(generations.fitness());
             generations.generation_number += 1;
             generations.neural_networks = Vec::new();
+
+            if generation_limit.is_some_and(|limit| generations.generation_number >= limit) {
+                exit.send(AppExit);
+            }
         }
 
-        for entity in query_collision.iter() {
-            commands.entity(entity).despawn();
+        for entity in query_obstacle.iter() {
+            commands.entity(entity).despawn_recursive();
         }
+        pipe_spawn_settings.restart(&config);
+    }
+}
+
+fn player_survival_system(mut query: Query<&mut FitnessRecord, With<Player>>) {
+    for mut fitness_record in query.iter_mut() {
+        fitness_record.frames_survived += 1;
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn player_generation_add_player_system(
+    win_size: Res<WinSize>,
+    config: Res<GameConfig>,
+    fitness_function: Res<FitnessFunction>,
     mut reader: EventReader<PlayerDieEvent>,
-    query: Query<(Entity, &NeuralNetwork), With<Player>>,
+    query: Query<
+        (
+            Entity,
+            Option<&NeuralNetwork>,
+            &FitnessRecord,
+            &Score,
+            &Transform,
+        ),
+        With<Player>,
+    >,
+    obstacle_query: Query<(&Transform, &Obstacle, &Velocity)>,
     mut commands: Commands,
     mut generations: ResMut<Generation>,
 ) {
@@ -70,39 +176,70 @@ fn player_generation_add_player_system(
         .iter()
         .map(|player_die_event| player_die_event.0)
         .collect();
-    for (entity, neural_network) in query.iter() {
-        if player_die_entities.contains(&entity) {
-            generations.neural_networks.push(neural_network.clone());
-            commands.entity(entity).despawn();
+    for (entity, neural_network, fitness_record, score, transform) in query.iter() {
+        if !player_die_entities.contains(&entity) {
+            continue;
         }
+
+        // Human players have no network to rank.
+        if let Some(neural_network) = neural_network {
+            let next_gap_y = upcoming_gaps(&config, transform.translation.x, obstacle_query.iter())
+                .first()
+                .map_or(0., |gap| gap.center);
+
+            let fitness_record = FitnessRecord {
+                pipes_passed: score.0,
+                gap_distance: (transform.translation.y - next_gap_y).abs() / (win_size.h / 2.),
+                ..*fitness_record
+            };
+
+            generations.add(
+                neural_network.clone(),
+                fitness_function.0.evaluate(&fitness_record),
+            );
+        }
+
+        commands.entity(entity).despawn();
     }
 }
 
 fn player_neural_network_feed_forward_system(
     win_size: Res<WinSize>,
-    mut query: Query<(&mut NeuralNetwork, &mut Velocity, &Transform), With<Player>>,
-    pipes_query: Query<&Transform, With<Pipe>>,
+    config: Res<GameConfig>,
+    mut query: Query<
+        (
+            &mut NeuralNetwork,
+            &mut Velocity,
+            &Transform,
+            &Controller,
+            &RayHits,
+        ),
+        With<Player>,
+    >,
+    obstacle_query: Query<(&Transform, &Obstacle, &Velocity), Without<Player>>,
 ) {
-    for (mut neural_network, mut velocity, transform) in query.iter_mut() {
-        let mut sorted_pipes: Vec<&Transform> = pipes_query.iter().collect();
-
-        let player_position = transform.translation.y;
-
-        let output = if sorted_pipes.len() >= 2 {
-            sorted_pipes.sort_by(|a, b| a.translation.x.partial_cmp(&b.translation.x).unwrap());
-            neural_network.feed_forward(vec![
-                player_position / (win_size.h / 2.),
-                ((sorted_pipes[0].translation.y + sorted_pipes[1].translation.y) / 2.)
-                    / (win_size.h / 2.),
-                sorted_pipes[0].translation.x / (win_size.w / 2.),
-            ])[0]
-                == 1.
-        } else {
-            neural_network.feed_forward(vec![player_position / (win_size.h / 2.), 0., 0.])[0] == 1.
+    for (mut neural_network, mut velocity, transform, controller, ray_hits) in query.iter_mut() {
+        if *controller != Controller::NeuralNetwork {
+            continue;
+        }
+
+        let gaps = upcoming_gaps(&config, transform.translation.x, obstacle_query.iter());
+
+        let bird = BirdView {
+            transform,
+            velocity: &velocity,
+            gaps: &gaps,
+            ray_hits: &ray_hits.0,
         };
+        let mut inputs = Vec::with_capacity(config.brain.input_count());
+        for sensor in &config.brain.sensors {
+            sensor.read(&config, &win_size, &bird, &mut inputs);
+        }
 
-        if output {
-            velocity.y = 0.65 * BASE_SPEED;
+        // Jumps when the output sum beats its bias, whatever the output activation.
+        let jump_threshold = neural_network.output_activation().threshold();
+        if neural_network.feed_forward(inputs)[0] > jump_threshold {
+            velocity.y = config.jump_speed();
         }
     }
 }
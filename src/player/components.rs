@@ -1,7 +1,33 @@
 use bevy::prelude::Component;
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::Inspectable;
 
 #[derive(Component)]
 pub struct Player;
 
 #[derive(Component)]
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 pub struct Score(pub u32);
+
+// Id of the last obstacle this player scored on. Obstacles come in id order,
+// so anything up to it is already counted.
+#[derive(Component, Default)]
+pub struct PassedGates {
+    pub last: Option<u32>,
+}
+
+impl PassedGates {
+    pub fn has_passed(&self, id: u32) -> bool {
+        self.last.is_some_and(|last| id <= last)
+    }
+}
+
+// Who decides when a player jumps.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Controller {
+    Human,
+    // Needs a `NeuralNetwork` component on the same entity.
+    NeuralNetwork,
+    // Built-in autopilot aiming for the middle of the next gap.
+    Script,
+}
@@ -6,5 +6,6 @@ pub struct PlayerDieEvent(pub Entity);
 
 pub struct SpawnPlayers {
     pub number: u32,
-    pub neural_network: Option<NeuralNetwork>,
+    // Players past the end of the list get a random network.
+    pub neural_networks: Vec<NeuralNetwork>,
 }
@@ -3,4 +3,6 @@ pub mod components;
 pub mod events;
 pub mod movement_plugin;
 pub mod plugin;
+pub mod sensors;
 pub mod spawn_plugin;
+pub mod vision_plugin;
@@ -1,9 +1,9 @@
-use bevy::{
-    prelude::{Plugin, Query, Res, Transform, With},
-    time::Time,
-};
+use bevy::prelude::{ParallelSystemDescriptorCoercion, Plugin, Query, Transform, With};
 
-use crate::components::Velocity;
+use crate::{
+    components::Velocity,
+    simulation::{SimulationLabel, SimulationStage, FIXED_TIMESTEP},
+};
 
 use super::components::Player;
 
@@ -11,17 +11,18 @@ pub struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system(player_movement_system);
+        app.add_system_to_stage(
+            SimulationStage,
+            player_movement_system
+                .label(SimulationLabel::Movement)
+                .after(SimulationLabel::Physics),
+        );
     }
 }
 
-fn player_movement_system(
-    time: Res<Time>,
-    mut query: Query<(&Velocity, &mut Transform), With<Player>>,
-) {
+fn player_movement_system(mut query: Query<(&Velocity, &mut Transform), With<Player>>) {
     for (velocity, mut transform) in query.iter_mut() {
         let translation = &mut transform.translation;
-        let delta = time.delta_seconds();
-        translation.y += velocity.y * delta;
+        translation.y += velocity.y * FIXED_TIMESTEP;
     }
 }
@@ -1,13 +1,20 @@
 use bevy::prelude::*;
 
 use crate::{
-    components::{AnimationTimer, Velocity},
-    GameTextures, WinSize, BASE_SPEED, PLAYER_SIZE, PLAYER_SPRITE_SCALE,
+    components::{AnimationTimer, Obstacle, Velocity},
+    config::GameConfig,
+    pipe::upcoming_gaps,
+    simulation::{SimulationLabel, SimulationStage},
+    GameStates, GameTextures, WinSize,
 };
 
 use super::{
-    brain_plugin::BrainPlugin, components::Player, events::PlayerDieEvent,
-    movement_plugin::MovementPlugin, spawn_plugin::SpawnPlugin,
+    brain_plugin::BrainPlugin,
+    components::{Controller, Player},
+    events::PlayerDieEvent,
+    movement_plugin::MovementPlugin,
+    spawn_plugin::SpawnPlugin,
+    vision_plugin::VisionPlugin,
 };
 
 pub struct PlayerPlugin;
@@ -17,29 +24,72 @@ impl Plugin for PlayerPlugin {
         app.add_plugin(MovementPlugin)
             .add_plugin(SpawnPlugin)
             .add_plugin(BrainPlugin)
-            .add_system(player_keyboard_event_system)
+            .add_plugin(VisionPlugin)
+            .add_system_set(
+                SystemSet::on_update(GameStates::Playing).with_system(player_keyboard_event_system),
+            )
             .add_system(player_animation_system)
-            .add_system(check_player_border_overflow_system);
+            .add_system_to_stage(
+                SimulationStage,
+                player_script_system.label(SimulationLabel::Decision),
+            )
+            .add_system_to_stage(
+                SimulationStage,
+                check_player_border_overflow_system
+                    .label(SimulationLabel::Collision)
+                    .after(SimulationLabel::Movement),
+            );
     }
 }
 
 fn player_keyboard_event_system(
     kb: Res<Input<KeyCode>>,
-    mut query_player_velocity: Query<&mut Velocity, With<Player>>,
+    config: Res<GameConfig>,
+    mut query_player_velocity: Query<(&mut Velocity, &Controller), With<Player>>,
 ) {
-    for mut velocity in query_player_velocity.iter_mut() {
-        if kb.just_pressed(KeyCode::Space) {
-            velocity.y = 0.65 * BASE_SPEED;
+    if !kb.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    for (mut velocity, controller) in query_player_velocity.iter_mut() {
+        if *controller == Controller::Human {
+            velocity.y = config.jump_speed();
+        }
+    }
+}
+
+// Jumps whenever the bird falls below the middle of the next gap.
+fn player_script_system(
+    config: Res<GameConfig>,
+    mut query: Query<(&mut Velocity, &Transform, &Controller), With<Player>>,
+    obstacle_query: Query<(&Transform, &Obstacle, &Velocity), Without<Player>>,
+) {
+    for (mut velocity, transform, controller) in query.iter_mut() {
+        if *controller != Controller::Script {
+            continue;
+        }
+
+        let next_gap_y = upcoming_gaps(&config, transform.translation.x, obstacle_query.iter())
+            .first()
+            .map_or(0., |gap| gap.center);
+
+        if transform.translation.y < next_gap_y && velocity.y <= 0. {
+            velocity.y = config.jump_speed();
         }
     }
 }
 
 fn player_animation_system(
     time: Res<Time>,
-    game_textures: Res<GameTextures>,
-    texture_atlases: Res<Assets<TextureAtlas>>,
+    game_textures: Option<Res<GameTextures>>,
+    texture_atlases: Option<Res<Assets<TextureAtlas>>>,
     mut query: Query<(&mut AnimationTimer, &mut TextureAtlasSprite), With<Player>>,
 ) {
+    let (game_textures, texture_atlases) = match (game_textures, texture_atlases) {
+        (Some(game_textures), Some(texture_atlases)) => (game_textures, texture_atlases),
+        _ => return,
+    };
+
     for (mut timer, mut sprite) in query.iter_mut() {
         timer.timer.tick(time.delta());
         if timer.timer.just_finished() {
@@ -51,13 +101,15 @@ fn player_animation_system(
 
 fn check_player_border_overflow_system(
     win_size: Res<WinSize>,
+    config: Res<GameConfig>,
     query: Query<(Entity, &Transform), With<Player>>,
     mut write: EventWriter<PlayerDieEvent>,
 ) {
     for (entity, transform) in query.iter() {
         let translation = transform.translation;
-        if translation.y > win_size.h / 2. - PLAYER_SIZE.1 / 2. * PLAYER_SPRITE_SCALE
-            || translation.y < -win_size.h / 2. + PLAYER_SIZE.1 / 2. * PLAYER_SPRITE_SCALE
+        let half_height = config.player_extent().y / 2.;
+        if translation.y > win_size.h / 2. - half_height
+            || translation.y < -win_size.h / 2. + half_height
         {
             write.send(PlayerDieEvent(entity));
         }
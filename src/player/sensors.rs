@@ -0,0 +1,73 @@
+use bevy::prelude::Transform;
+use serde::{Deserialize, Serialize};
+
+use crate::{components::Velocity, config::GameConfig, pipe::UpcomingGap, WinSize};
+
+// One network input, picked in the `brain` section of the config file.
+// Positions are scaled by half the window so that values stay around [-1, 1].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sensor {
+    BirdY,
+    // In jumps, 1 right after a jump.
+    BirdVelocity,
+    NextGapCenter,
+    NextGapTop,
+    NextGapBottom,
+    NextPipeDistance,
+    SecondPipeDistance,
+    // Scrolling speed, as a fraction of the base speed.
+    Speed,
+    // One input per ray of the `rays` config, see `VisionPlugin`.
+    Rays,
+}
+
+// What a bird knows about the world at one step.
+pub struct BirdView<'a> {
+    pub transform: &'a Transform,
+    pub velocity: &'a Velocity,
+    // Sorted nearest first.
+    pub gaps: &'a [UpcomingGap],
+    pub ray_hits: &'a [f32],
+}
+
+impl Sensor {
+    // Pushes the inputs of this sensor, a missing gap reads as 0.
+    pub fn read(
+        self,
+        config: &GameConfig,
+        win_size: &WinSize,
+        bird: &BirdView,
+        inputs: &mut Vec<f32>,
+    ) {
+        let BirdView {
+            transform,
+            velocity,
+            gaps,
+            ray_hits,
+        } = *bird;
+        let half_height = win_size.h / 2.;
+        let half_width = win_size.w / 2.;
+        let next = gaps.first();
+
+        let input = match self {
+            Sensor::BirdY => transform.translation.y / half_height,
+            Sensor::BirdVelocity => velocity.y / config.jump_speed(),
+            Sensor::NextGapCenter => next.map_or(0., |gap| gap.center / half_height),
+            Sensor::NextGapTop => next.map_or(0., |gap| gap.top / half_height),
+            Sensor::NextGapBottom => next.map_or(0., |gap| gap.bottom / half_height),
+            Sensor::NextPipeDistance => {
+                next.map_or(0., |gap| (gap.x - transform.translation.x) / half_width)
+            }
+            Sensor::SecondPipeDistance => gaps
+                .get(1)
+                .map_or(0., |gap| (gap.x - transform.translation.x) / half_width),
+            Sensor::Speed => next.map_or(-config.pipe_velocity, |gap| gap.speed),
+            Sensor::Rays => {
+                inputs.extend_from_slice(ray_hits);
+                return;
+            }
+        };
+        inputs.push(input);
+    }
+}
@@ -1,93 +1,173 @@
-use std::{fs, path::Path};
-
 use bevy::{
-    prelude::{Commands, EventReader, Plugin, Res, StartupStage, Transform, Vec3},
+    prelude::{
+        Commands, Entity, EventReader, ParallelSystemDescriptorCoercion, Plugin, Res, ResMut,
+        SystemSet, Transform, TransformBundle, Vec3,
+    },
     sprite::SpriteSheetBundle,
     time::Timer,
 };
 
 use crate::{
-    components::{AffectedByGravity, AnimationTimer, Velocity},
-    neural_networks::brain::NeuralNetwork,
-    GameTextures, PLAYER_SPRITE_SCALE,
+    components::{AffectedByGravity, AnimationTimer, Interpolated, Velocity},
+    config::GameConfig,
+    neural_networks::{brain::NeuralNetwork, fitness::FitnessRecord, generation::Generation},
+    rng::GameRng,
+    simulation::{SimulationLabel, SimulationStage},
+    GameStates, GameTextures, RunMode,
 };
 
 use super::{
-    components::{Player, Score},
+    components::{Controller, PassedGates, Player, Score},
     events::SpawnPlayers,
+    vision_plugin::RayHits,
 };
 
 pub struct SpawnPlugin;
 
 impl Plugin for SpawnPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_startup_system_to_stage(StartupStage::PostStartup, player_spawn_system)
-            .add_system(player_spawn_handle_system);
+        app.add_system_set(
+            SystemSet::on_enter(GameStates::Playing).with_system(player_spawn_system),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            player_spawn_handle_system.label(SimulationLabel::Decision),
+        );
     }
 }
 
 fn player_spawn_handle_system(
     mut commands: Commands,
-    game_textures: Res<GameTextures>,
+    config: Res<GameConfig>,
+    game_textures: Option<Res<GameTextures>>,
+    mut game_rng: ResMut<GameRng>,
     mut reader: EventReader<SpawnPlayers>,
 ) {
     for spawn_players in reader.iter() {
         for i in 0..spawn_players.number {
-            let neural_network = spawn_players.neural_network.clone();
-            if let Some(mut nn) = neural_network {
-                if i != 0 {
-                    nn.mutate(0.10);
-                }
-                spawn_player(&mut commands, &game_textures, Some(nn));
-            } else {
-                spawn_player(&mut commands, &game_textures, None);
-            }
+            let neural_network = spawn_players
+                .neural_networks
+                .get(i as usize)
+                .cloned()
+                .unwrap_or_else(|| random_neural_network(&config, &mut game_rng));
+            spawn_neural_network_player(
+                &mut commands,
+                &config,
+                game_textures.as_deref(),
+                neural_network,
+            );
         }
     }
 }
 
-fn player_spawn_system(mut commands: Commands, game_textures: Res<GameTextures>) {
-    if Path::new("neural_network_save.json").exists() {
-        let data =
-            fs::read_to_string("neural_network_save.json").expect("Unable to read save file.");
-        let neural_network =
-            serde_json::from_str::<NeuralNetwork>(&data).expect("Unable to parse json file");
-        spawn_player(&mut commands, &game_textures, Some(neural_network));
-    } else {
-        spawn_player(&mut commands, &game_textures, None);
+fn random_neural_network(config: &GameConfig, game_rng: &mut GameRng) -> NeuralNetwork {
+    NeuralNetwork::new(
+        config.brain.topology(),
+        config.brain.activations(),
+        &mut game_rng.weights,
+    )
+}
+
+fn player_spawn_system(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    game_textures: Option<Res<GameTextures>>,
+    mut game_rng: ResMut<GameRng>,
+    generation: Res<Generation>,
+    run_mode: Res<RunMode>,
+) {
+    let game_textures = game_textures.as_deref();
+
+    match &*run_mode {
+        // Resumed from a checkpoint: the next population is bred on the first step.
+        RunMode::Train { .. } if !generation.neural_networks.is_empty() => {}
+        RunMode::Train { saved, .. } => {
+            let neural_network = saved
+                .clone()
+                .unwrap_or_else(|| random_neural_network(&config, &mut game_rng));
+            spawn_neural_network_player(&mut commands, &config, game_textures, neural_network);
+        }
+        RunMode::Play { autopilot } => {
+            let controller = if *autopilot {
+                Controller::Script
+            } else {
+                Controller::Human
+            };
+            spawn_player(&mut commands, &config, game_textures, controller);
+        }
+        RunMode::Race(neural_network) => {
+            spawn_player(&mut commands, &config, game_textures, Controller::Human);
+            spawn_neural_network_player(
+                &mut commands,
+                &config,
+                game_textures,
+                neural_network.clone(),
+            );
+        }
+        RunMode::Watch(neural_network) | RunMode::Eval { neural_network, .. } => {
+            spawn_neural_network_player(
+                &mut commands,
+                &config,
+                game_textures,
+                neural_network.clone(),
+            );
+        }
     }
 }
 
-fn spawn_player(
+pub fn spawn_neural_network_player(
     commands: &mut Commands,
-    game_textures: &Res<GameTextures>,
-    neural_network: Option<NeuralNetwork>,
+    config: &GameConfig,
+    game_textures: Option<&GameTextures>,
+    neural_network: NeuralNetwork,
 ) {
-    commands
-        .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: game_textures.player.clone(),
-            transform: Transform {
-                translation: Vec3 {
-                    x: 0.,
-                    y: 0.,
-                    z: 10.,
-                },
-                scale: Vec3 {
-                    x: PLAYER_SPRITE_SCALE,
-                    y: PLAYER_SPRITE_SCALE,
-                    z: 1.,
-                },
+    let entity = spawn_player(commands, config, game_textures, Controller::NeuralNetwork);
+    commands.entity(entity).insert(neural_network);
+}
+
+// Without textures (headless mode) the player only gets a transform.
+pub fn spawn_player(
+    commands: &mut Commands,
+    config: &GameConfig,
+    game_textures: Option<&GameTextures>,
+    controller: Controller,
+) -> Entity {
+    let transform = Transform {
+        translation: Vec3 {
+            x: 0.,
+            y: 0.,
+            z: 10.,
+        },
+        scale: Vec3 {
+            x: config.player_sprite_scale,
+            y: config.player_sprite_scale,
+            z: 1.,
+        },
+
+        ..Default::default()
+    };
 
-                ..Default::default()
-            },
+    let mut player = match game_textures {
+        Some(game_textures) => commands.spawn_bundle(SpriteSheetBundle {
+            texture_atlas: game_textures.player.clone(),
+            transform,
             ..Default::default()
-        })
+        }),
+        None => commands.spawn_bundle(TransformBundle::from_transform(transform)),
+    };
+
+    player
         .insert(Player)
+        .insert(controller)
+        .insert(Interpolated::new(transform.translation))
         .insert(Velocity { x: 0., y: 0. })
         .insert(AffectedByGravity { is_affected: true })
         .insert(AnimationTimer {
             timer: Timer::from_seconds(0.1, true),
         })
+        .insert(RayHits::default())
         .insert(Score(0))
-        .insert(neural_network.unwrap_or(NeuralNetwork::new(vec![3, 6, 1])));
+        .insert(PassedGates::default())
+        .insert(FitnessRecord::default())
+        .id()
 }
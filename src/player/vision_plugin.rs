@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::collider_extent,
+    components::{Collider, Obstacle},
+    config::GameConfig,
+    pipe::part_translation,
+    simulation::{SimulationLabel, SimulationStage},
+    WinSize,
+};
+
+use super::components::Player;
+
+// Casts the rays of the `rays` config from every bird against the pipes and
+// the screen borders.
+pub struct VisionPlugin;
+
+// Distance to the first hit along each ray, as a fraction of the ray length.
+// 1 means nothing within reach.
+#[derive(Component, Default)]
+pub struct RayHits(pub Vec<f32>);
+
+impl Plugin for VisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            SimulationStage,
+            player_raycast_system
+                .label(SimulationLabel::Sensing)
+                .after(SimulationLabel::Snapshot)
+                .before(SimulationLabel::Decision),
+        );
+    }
+}
+
+fn player_raycast_system(
+    config: Res<GameConfig>,
+    win_size: Res<WinSize>,
+    mut query: Query<(&Transform, &mut RayHits), With<Player>>,
+    collider_query: Query<(&Transform, &Collider, &Parent)>,
+    obstacle_query: Query<(&Transform, &Obstacle)>,
+) {
+    let directions = config.brain.rays.directions();
+    let length = config.brain.rays.length;
+    let half_screen = Vec2::new(win_size.w / 2., win_size.h / 2.);
+
+    // Centers and half sizes of every pipe.
+    let pipe_boxes: Vec<(Vec2, Vec2)> = collider_query
+        .iter()
+        .filter(|(_, collider, _)| matches!(collider, Collider::Loss))
+        .filter_map(|(collider_transform, collider, parent)| {
+            let (obstacle_transform, obstacle) = obstacle_query.get(parent.get()).ok()?;
+            Some((
+                part_translation(obstacle_transform, collider_transform).truncate(),
+                collider_extent(&config, obstacle, collider) / 2.,
+            ))
+        })
+        .collect();
+
+    for (transform, mut ray_hits) in query.iter_mut() {
+        let origin = transform.translation.truncate();
+
+        ray_hits.0 = directions
+            .iter()
+            .map(|direction| {
+                let pipe_distance = pipe_boxes
+                    .iter()
+                    .filter_map(|(center, half_extent)| {
+                        ray_box_distance(origin, *direction, *center, *half_extent)
+                    })
+                    .fold(f32::INFINITY, f32::min);
+                let border_distance = ray_border_distance(origin, *direction, half_screen);
+
+                pipe_distance.min(border_distance).min(length) / length
+            })
+            .collect();
+    }
+}
+
+// Slab test: distance along the ray to the box, if the ray goes through it.
+fn ray_box_distance(origin: Vec2, direction: Vec2, center: Vec2, half_extent: Vec2) -> Option<f32> {
+    let inverse = Vec2::ONE / direction;
+    let t1 = (center - half_extent - origin) * inverse;
+    let t2 = (center + half_extent - origin) * inverse;
+    let near = t1.min(t2).max_element().max(0.);
+    let far = t1.max(t2).min_element();
+
+    (far >= near).then_some(near)
+}
+
+// The bird is inside the screen, so the ray always leaves it somewhere.
+fn ray_border_distance(origin: Vec2, direction: Vec2, half_screen: Vec2) -> f32 {
+    let inverse = Vec2::ONE / direction;
+    let t1 = (-half_screen - origin) * inverse;
+    let t2 = (half_screen - origin) * inverse;
+
+    t1.max(t2).min_element().max(0.)
+}
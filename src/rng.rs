@@ -0,0 +1,38 @@
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+// Each consumer draws from its own stream of the same seed, so spawning more
+// birds or mutating differently never shifts the pipe sequence.
+const PIPES_STREAM: u64 = 0;
+const WEIGHTS_STREAM: u64 = 1;
+const MUTATION_STREAM: u64 = 2;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameRng {
+    pub seed: u64,
+    pub pipes: ChaCha8Rng,
+    pub weights: ChaCha8Rng,
+    pub mutation: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> GameRng {
+        GameRng {
+            seed,
+            pipes: stream(seed, PIPES_STREAM),
+            weights: stream(seed, WEIGHTS_STREAM),
+            mutation: stream(seed, MUTATION_STREAM),
+        }
+    }
+
+    pub fn from_entropy() -> GameRng {
+        GameRng::from_seed(rand::thread_rng().next_u64())
+    }
+}
+
+fn stream(seed: u64, stream: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
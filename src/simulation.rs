@@ -0,0 +1,200 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*, transform::TransformSystem};
+
+use crate::{components::Interpolated, GameStates};
+
+// Every physics, collision and brain system advances the world by exactly this much.
+pub const FIXED_TIMESTEP: f32 = 1. / 60.;
+
+// Longest frame the simulation catches up on, so a stall does not snowball.
+const MAX_FRAME_TIME: f64 = 0.25;
+
+// Real time spent stepping per rendered frame at unlimited speed.
+const UNLIMITED_FRAME_BUDGET: Duration = Duration::from_millis(33);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct SimulationStage;
+
+// Ordering of one simulation step, each label runs after the previous one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum SimulationLabel {
+    Snapshot,
+    Sensing,
+    Decision,
+    Physics,
+    Movement,
+    Collision,
+    Evolution,
+    Generation,
+    Store,
+}
+
+// How many simulated seconds pass per real second.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimulationSpeed {
+    Multiplier(f32),
+    // Steps back to back, only pausing to render a frame now and then.
+    Unlimited,
+}
+
+impl Default for SimulationSpeed {
+    fn default() -> Self {
+        SimulationSpeed::Multiplier(1.)
+    }
+}
+
+impl fmt::Display for SimulationSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationSpeed::Multiplier(multiplier) => write!(f, "{}x", multiplier),
+            SimulationSpeed::Unlimited => write!(f, "max"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SimulationClock {
+    accumulator: f64,
+    looping: bool,
+    frame_start: Option<Instant>,
+    pub steps: u64,
+}
+
+impl SimulationClock {
+    // How far the current frame is between the last two steps, used to interpolate rendering.
+    pub fn overstep_percentage(&self) -> f32 {
+        (self.accumulator / FIXED_TIMESTEP as f64) as f32
+    }
+}
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationClock>()
+            .init_resource::<SimulationSpeed>()
+            .add_stage_after(
+                CoreStage::Update,
+                SimulationStage,
+                SystemStage::parallel().with_run_criteria(fixed_timestep_run_criteria),
+            )
+            .add_system(simulation_speed_keyboard_system)
+            .add_system_to_stage(CoreStage::PreUpdate, restore_simulated_translation_system)
+            .add_system_to_stage(
+                SimulationStage,
+                snapshot_translation_system
+                    .label(SimulationLabel::Snapshot)
+                    .before(SimulationLabel::Decision),
+            )
+            .add_system_to_stage(
+                SimulationStage,
+                store_translation_system
+                    .label(SimulationLabel::Store)
+                    .after(SimulationLabel::Generation),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_translation_system.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+fn fixed_timestep_run_criteria(
+    time: Res<Time>,
+    speed: Res<SimulationSpeed>,
+    state: Res<State<GameStates>>,
+    mut clock: ResMut<SimulationClock>,
+) -> ShouldRun {
+    // The world stays frozen on the start and game over screens.
+    if state.current() != &GameStates::Playing {
+        clock.looping = false;
+        return ShouldRun::No;
+    }
+
+    let should_step = match *speed {
+        SimulationSpeed::Multiplier(multiplier) => {
+            let multiplier = multiplier as f64;
+            if !clock.looping {
+                clock.accumulator = (clock.accumulator + time.delta_seconds_f64() * multiplier)
+                    .min(MAX_FRAME_TIME * multiplier);
+            }
+
+            if clock.accumulator >= FIXED_TIMESTEP as f64 {
+                clock.accumulator -= FIXED_TIMESTEP as f64;
+                true
+            } else {
+                false
+            }
+        }
+        SimulationSpeed::Unlimited => {
+            if !clock.looping {
+                clock.accumulator = 0.;
+                clock.frame_start = Some(Instant::now());
+            }
+
+            clock
+                .frame_start
+                .is_some_and(|frame_start| frame_start.elapsed() < UNLIMITED_FRAME_BUDGET)
+        }
+    };
+
+    if should_step {
+        clock.steps += 1;
+        clock.looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        clock.looping = false;
+        ShouldRun::No
+    }
+}
+
+fn simulation_speed_keyboard_system(kb: Res<Input<KeyCode>>, mut speed: ResMut<SimulationSpeed>) {
+    let new_speed = if kb.just_pressed(KeyCode::Key1) {
+        SimulationSpeed::Multiplier(1.)
+    } else if kb.just_pressed(KeyCode::Key2) {
+        SimulationSpeed::Multiplier(2.)
+    } else if kb.just_pressed(KeyCode::Key3) {
+        SimulationSpeed::Multiplier(10.)
+    } else if kb.just_pressed(KeyCode::Key4) {
+        SimulationSpeed::Multiplier(100.)
+    } else if kb.just_pressed(KeyCode::Key5) {
+        SimulationSpeed::Unlimited
+    } else {
+        return;
+    };
+
+    *speed = new_speed;
+}
+
+// Puts back the simulated translation the renderer overwrote last frame.
+fn restore_simulated_translation_system(mut query: Query<(&Interpolated, &mut Transform)>) {
+    for (interpolated, mut transform) in query.iter_mut() {
+        transform.translation = interpolated.current;
+    }
+}
+
+fn snapshot_translation_system(mut query: Query<(&mut Interpolated, &Transform)>) {
+    for (mut interpolated, transform) in query.iter_mut() {
+        interpolated.previous = transform.translation;
+    }
+}
+
+fn store_translation_system(mut query: Query<(&mut Interpolated, &Transform)>) {
+    for (mut interpolated, transform) in query.iter_mut() {
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate_translation_system(
+    clock: Res<SimulationClock>,
+    mut query: Query<(&Interpolated, &mut Transform)>,
+) {
+    let overstep = clock.overstep_percentage();
+    for (interpolated, mut transform) in query.iter_mut() {
+        transform.translation = interpolated.previous.lerp(interpolated.current, overstep);
+    }
+}
@@ -1,13 +1,27 @@
 use bevy::prelude::*;
 
-use crate::{components::TextGameState, GameFont, GameState, GameStates};
+use crate::{
+    components::{TextGameState, TextScore, TextSimulationSpeed},
+    dashboard::DashboardPlugin,
+    episode::EpisodeStats,
+    high_score::HighScore,
+    player::components::{Controller, Player, Score},
+    simulation::SimulationSpeed,
+    GameFont, GameStates, PipeSpawnSettings, RunMode,
+};
 
 pub struct TextDisplayPlugin;
 
 impl Plugin for TextDisplayPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system_to_stage(StartupStage::PostStartup, text_display_startup_system)
-            .add_system(text_display_system);
+            .add_system(text_display_system)
+            .add_system(score_display_system)
+            .add_system(simulation_speed_display_system);
+
+        if matches!(app.world.resource::<RunMode>(), RunMode::Train { .. }) {
+            app.add_plugin(DashboardPlugin);
+        }
     }
 }
 
@@ -34,42 +48,104 @@ fn text_display_startup_system(mut commands: Commands, game_font: Res<GameFont>)
             game_over_display(parent, game_font.0.clone());
             start_screen_display(parent, game_font.0.clone());
         });
+
+    simulation_speed_display(&mut commands, game_font.0.clone());
 }
 
 fn text_display_system(
-    game_state: Res<GameState>,
-    mut query: Query<(&mut Text, &mut Visibility, &TextGameState), With<TextGameState>>,
-    // query_score: Query<&Score, With<Player>>,
+    state: Res<State<GameStates>>,
+    high_score: Res<HighScore>,
+    pipe_spawn_settings: Res<PipeSpawnSettings>,
+    episode_stats: Option<Res<EpisodeStats>>,
+    mut query: Query<(&mut Text, &mut Visibility, &TextGameState)>,
 ) {
-    // let max_score = query_score.iter().map(|score| score.0).max();
-    // let mut score_string: String = String::from("0");
-    // if let Some(x) = max_score {
-    //     score_string = x.to_string();
-    // }
+    if !state.is_changed() {
+        return;
+    }
+
     for (mut text, mut visibility, text_game_state) in query.iter_mut() {
-        if game_state.state != text_game_state.state {
-            visibility.is_visible = false;
-        } else {
-            visibility.is_visible = true;
-            text.sections[0].value = "".to_owned();
-            // match text_game_state.state {
-            //     // GameStates::Playing => text.sections[0].value = score_string.clone(),
-            //     // GameStates::GameOver => {
-            //     //     text.sections[0].value =
-            //     //         "Game Over, Score : ".to_owned() + &score_string.as_str() + "\nPress Space";
-            //     // }
-            //     _ => text.sections[0].value = "".to_owned(),
-            // }
+        visibility.is_visible = state.current() == &text_game_state.state;
+
+        if visibility.is_visible && text_game_state.state == GameStates::GameOver {
+            let score = episode_stats
+                .as_ref()
+                .and_then(|episode_stats| episode_stats.scores.last().copied())
+                .unwrap_or(0);
+            let title = if pipe_spawn_settings.course_complete() {
+                "Course Complete"
+            } else {
+                "Game Over"
+            };
+            text.sections[0].value = format!(
+                "{}\nScore: {}\nBest: {}\n<Press Space To Restart>",
+                title, score, high_score.best
+            );
         }
     }
 }
 
+// The score of the bird being played, or of the best living bird when networks fly alone.
+fn score_display_system(
+    run_mode: Res<RunMode>,
+    high_score: Res<HighScore>,
+    player_query: Query<(&Score, &Controller), With<Player>>,
+    mut query: Query<&mut Text, With<TextScore>>,
+) {
+    let score = player_query
+        .iter()
+        .filter(|(_, controller)| run_mode.keeps_score_of(**controller))
+        .map(|(score, _)| score.0)
+        .max()
+        .unwrap_or(0);
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!("{}\nBest: {}", score, high_score.best.max(score));
+    }
+}
+
+fn simulation_speed_display_system(
+    speed: Res<SimulationSpeed>,
+    mut query: Query<&mut Text, With<TextSimulationSpeed>>,
+) {
+    if !speed.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!("Speed: {}", *speed);
+    }
+}
+
+fn simulation_speed_display(commands: &mut Commands, game_font: Handle<Font>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "Speed: 1x",
+                TextStyle {
+                    font: game_font,
+                    font_size: 20.,
+                    color: Color::BLACK,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(5.),
+                    left: Val::Px(5.),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(TextSimulationSpeed);
+}
+
 fn playing_display(child_builder: &mut ChildBuilder, game_font: Handle<Font>) {
     child_builder
         .spawn_bundle(
             TextBundle::from_section(
                 // Accepts a `String` or any type that converts into a `String`, such as `&str`
-                "<Press Space To Start>",
+                "",
                 TextStyle {
                     font: game_font,
                     font_size: 50.,
@@ -88,7 +164,8 @@ fn playing_display(child_builder: &mut ChildBuilder, game_font: Handle<Font>) {
         )
         .insert(TextGameState {
             state: GameStates::Playing,
-        });
+        })
+        .insert(TextScore);
 }
 
 fn game_over_display(child_builder: &mut ChildBuilder, game_font: Handle<Font>) {
@@ -96,7 +173,7 @@ fn game_over_display(child_builder: &mut ChildBuilder, game_font: Handle<Font>)
         .spawn_bundle(
             TextBundle::from_section(
                 // Accepts a `String` or any type that converts into a `String`, such as `&str`
-                "<Press Space To Start>",
+                "Game Over\n<Press Space To Restart>",
                 TextStyle {
                     font: game_font,
                     font_size: 50.,
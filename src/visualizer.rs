@@ -0,0 +1,315 @@
+use bevy::{
+    prelude::{shape::Circle, *},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::{
+    config::GameConfig,
+    neural_networks::brain::NeuralNetwork,
+    player::components::{Player, Score},
+    WinSize,
+};
+
+// Area taken by the graph in the bottom left corner of the window.
+const GRAPH_SIZE: (f32, f32) = (180., 120.);
+const GRAPH_MARGIN: f32 = 15.;
+// Above pipes and players.
+const GRAPH_Z: f32 = 20.;
+
+const NEURON_RADIUS: f32 = 6.;
+// Added to the neuron radius for a bias of 1.
+const BIAS_RING_WIDTH: f32 = 4.;
+// Added to the edge thickness for a weight of 1.
+const EDGE_MAX_THICKNESS: f32 = 3.;
+
+const POSITIVE_COLOR: Color = Color::rgb(0.2, 0.8, 0.2);
+const NEGATIVE_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+
+// Draws the network of the clicked bird, or of the best living one when
+// nothing is selected.
+pub struct VisualizerPlugin;
+
+#[derive(Default)]
+struct SelectedPlayer(Option<Entity>);
+
+// Player whose network is drawn this frame.
+#[derive(Default)]
+struct VisualizedPlayer(Option<Entity>);
+
+// Graph currently spawned, with no neurons when there is none.
+#[derive(Default)]
+struct VisualizerGraph {
+    // Neurons per layer.
+    shape: Vec<usize>,
+    win_size: (f32, f32),
+}
+
+struct VisualizerAssets {
+    circle: Handle<Mesh>,
+}
+
+#[derive(Component)]
+struct GraphEntity;
+
+// Layer 0 holds the network inputs, layer `n` the outputs of level `n - 1`.
+#[derive(Component)]
+struct GraphNeuron {
+    layer: usize,
+    index: usize,
+}
+
+#[derive(Component)]
+struct GraphBias {
+    layer: usize,
+    index: usize,
+}
+
+#[derive(Component)]
+struct GraphEdge {
+    level: usize,
+    from: usize,
+    to: usize,
+    length: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+enum VisualizerLabel {
+    Select,
+    Target,
+}
+
+impl Plugin for VisualizerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedPlayer>()
+            .init_resource::<VisualizedPlayer>()
+            .init_resource::<VisualizerGraph>()
+            .add_startup_system(visualizer_startup_system)
+            .add_system(player_select_system.label(VisualizerLabel::Select))
+            .add_system(
+                visualized_player_system
+                    .label(VisualizerLabel::Target)
+                    .after(VisualizerLabel::Select),
+            )
+            .add_system(visualizer_layout_system.after(VisualizerLabel::Target))
+            .add_system(visualizer_update_system.after(VisualizerLabel::Target));
+    }
+}
+
+fn visualizer_startup_system(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(VisualizerAssets {
+        circle: meshes.add(Mesh::from(Circle::new(1.))),
+    });
+}
+
+fn player_select_system(
+    mouse: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    win_size: Res<WinSize>,
+    config: Res<GameConfig>,
+    mut selected: ResMut<SelectedPlayer>,
+    query: Query<(Entity, &Transform), (With<Player>, With<NeuralNetwork>)>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let cursor = match windows
+        .get_primary()
+        .and_then(|window| window.cursor_position())
+    {
+        Some(cursor) => cursor,
+        None => return,
+    };
+
+    // The camera sits at the origin, the cursor is measured from the bottom left corner.
+    let click = cursor - Vec2::new(win_size.w / 2., win_size.h / 2.);
+    let reach = config.player_extent().y / 2.;
+
+    // Clicking the sky clears the selection.
+    selected.0 = query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate().distance(click)))
+        .filter(|(_, distance)| *distance <= reach)
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(entity, _)| entity);
+}
+
+fn visualized_player_system(
+    selected: Res<SelectedPlayer>,
+    mut visualized: ResMut<VisualizedPlayer>,
+    query: Query<(Entity, &Score), (With<Player>, With<NeuralNetwork>)>,
+) {
+    visualized.0 = selected
+        .0
+        .filter(|entity| query.get(*entity).is_ok())
+        .or_else(|| {
+            query
+                .iter()
+                .max_by_key(|(_, score)| score.0)
+                .map(|(entity, _)| entity)
+        });
+}
+
+// Respawns the graph when the drawn network has a different shape.
+#[allow(clippy::too_many_arguments)]
+fn visualizer_layout_system(
+    mut commands: Commands,
+    win_size: Res<WinSize>,
+    assets: Res<VisualizerAssets>,
+    visualized: Res<VisualizedPlayer>,
+    mut graph: ResMut<VisualizerGraph>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    network_query: Query<&NeuralNetwork>,
+    graph_query: Query<Entity, With<GraphEntity>>,
+) {
+    let shape = visualized
+        .0
+        .and_then(|entity| network_query.get(entity).ok())
+        .map_or_else(Vec::new, network_shape);
+    if shape == graph.shape && graph.win_size == (win_size.w, win_size.h) {
+        return;
+    }
+
+    for entity in graph_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for (level, window) in shape.windows(2).enumerate() {
+        for from in 0..window[0] {
+            for to in 0..window[1] {
+                let start = neuron_position(&win_size, &shape, level, from);
+                let end = neuron_position(&win_size, &shape, level + 1, to);
+                let delta = end - start;
+
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        transform: Transform {
+                            translation: ((start + end) / 2.).extend(GRAPH_Z),
+                            rotation: Quat::from_rotation_z(delta.y.atan2(delta.x)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(GraphEdge {
+                        level,
+                        from,
+                        to,
+                        length: delta.length(),
+                    })
+                    .insert(GraphEntity);
+            }
+        }
+    }
+
+    for (layer, count) in shape.iter().enumerate() {
+        for index in 0..*count {
+            let position = neuron_position(&win_size, &shape, layer, index);
+
+            if layer > 0 {
+                commands
+                    .spawn_bundle(MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(assets.circle.clone()),
+                        material: materials.add(Color::rgba(0., 0., 0., 0.).into()),
+                        transform: Transform::from_translation(position.extend(GRAPH_Z + 1.)),
+                        ..default()
+                    })
+                    .insert(GraphBias { layer, index })
+                    .insert(GraphEntity);
+            }
+
+            commands
+                .spawn_bundle(MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(assets.circle.clone()),
+                    material: materials.add(Color::BLACK.into()),
+                    transform: Transform::from_translation(position.extend(GRAPH_Z + 2.))
+                        .with_scale(Vec3::splat(NEURON_RADIUS)),
+                    ..default()
+                })
+                .insert(GraphNeuron { layer, index })
+                .insert(GraphEntity);
+        }
+    }
+
+    graph.shape = shape;
+    graph.win_size = (win_size.w, win_size.h);
+}
+
+fn visualizer_update_system(
+    visualized: Res<VisualizedPlayer>,
+    graph: Res<VisualizerGraph>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    network_query: Query<&NeuralNetwork>,
+    neuron_query: Query<(&GraphNeuron, &Handle<ColorMaterial>)>,
+    mut bias_query: Query<(&GraphBias, &mut Transform, &Handle<ColorMaterial>)>,
+    mut edge_query: Query<(&GraphEdge, &mut Sprite)>,
+) {
+    let neural_network = match visualized
+        .0
+        .and_then(|entity| network_query.get(entity).ok())
+    {
+        // Skipped for a frame while the graph is respawned to the new shape.
+        Some(neural_network) if network_shape(neural_network) == graph.shape => neural_network,
+        _ => return,
+    };
+    let levels = &neural_network.levels;
+
+    for (neuron, material) in neuron_query.iter() {
+        let value = if neuron.layer == 0 {
+            levels[0].inputs()[neuron.index]
+        } else {
+            levels[neuron.layer - 1].outputs()[neuron.index]
+        };
+        let brightness = (value.clamp(-1., 1.) + 1.) / 2.;
+
+        if let Some(material) = materials.get_mut(material) {
+            material.color = Color::rgb(brightness, brightness, brightness);
+        }
+    }
+
+    for (bias, mut transform, material) in bias_query.iter_mut() {
+        let value = levels[bias.layer - 1].biases()[bias.index];
+        transform.scale = Vec3::splat(NEURON_RADIUS + value.abs().min(1.) * BIAS_RING_WIDTH);
+
+        if let Some(material) = materials.get_mut(material) {
+            material.color = signed_color(value);
+        }
+    }
+
+    for (edge, mut sprite) in edge_query.iter_mut() {
+        let weight = levels[edge.level].weights()[edge.from][edge.to];
+        let strength = weight.abs().min(1.);
+
+        let mut color = signed_color(weight);
+        color.set_a(0.2 + 0.8 * strength);
+        sprite.color = color;
+        sprite.custom_size = Some(Vec2::new(edge.length, 1. + strength * EDGE_MAX_THICKNESS));
+    }
+}
+
+fn network_shape(neural_network: &NeuralNetwork) -> Vec<usize> {
+    let mut shape: Vec<usize> = neural_network
+        .levels
+        .iter()
+        .map(|level| level.shape().0)
+        .collect();
+    shape.extend(neural_network.levels.last().map(|level| level.shape().1));
+    shape
+}
+
+fn neuron_position(win_size: &WinSize, shape: &[usize], layer: usize, index: usize) -> Vec2 {
+    let origin = Vec2::new(
+        -win_size.w / 2. + GRAPH_MARGIN,
+        -win_size.h / 2. + GRAPH_MARGIN,
+    );
+    let x = GRAPH_SIZE.0 * layer as f32 / (shape.len() - 1).max(1) as f32;
+    // Each layer is centered vertically.
+    let y = GRAPH_SIZE.1 * (index as f32 + 0.5) / shape[layer] as f32;
+    origin + Vec2::new(x, y)
+}
+
+fn signed_color(value: f32) -> Color {
+    if value >= 0. {
+        POSITIVE_COLOR
+    } else {
+        NEGATIVE_COLOR
+    }
+}